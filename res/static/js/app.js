@@ -47,6 +47,21 @@ const NOTIFY_SETTINGS = {
  */
 const VISUALIZER_INTERVAL = 50;
 
+/**
+ * The size in pixels of each interaction zone view drawn on the visualizer.
+ */
+const ZONE_VIEW_SIZE = 100;
+
+/**
+ * The distance in millimeters around the sensor shown in each interaction zone view.
+ */
+const ZONE_VIEW_RANGE = 400;
+
+/**
+ * The polling rate/interval of the sensor status in milliseconds.
+ */
+const SENSOR_STATUS_INTERVAL = 1000;
+
 /**
  * The current gesture controller state.
  */
@@ -74,6 +89,11 @@ $('#clear_visual').on('click', function() {
     setShowClearVisualize(false);
 });
 
+$('#kind').on('change', function() {
+    // Only show gesture options when saving a gesture
+    $('.gesture-options').css('display', $(this).val() === 'gesture' ? 'block' : 'none');
+});
+
 $('#save_recording').on('click', function() {
     // Get the name
     let name = $('#name').val();
@@ -83,6 +103,22 @@ $('#save_recording').on('click', function() {
         alert("Please provide a template name");
         return;
     }
+
+    // Save postures from the current hand pose
+    if($('#kind').val() === 'posture') {
+        axios.get('/api/v1/posture/create/' + encodeURIComponent(name))
+            .then(function(response) {
+                if(!response.data)
+                    alert("No hand in view to save the posture from");
+                updateTemplateList();
+                sendState(STATE_NORMAL);
+            })
+            .catch(function(error) {
+                alert("An error occurred while saving your posture");
+                console.log(error);
+            });
+        return;
+    }
     if(trim === undefined || trim[0] < 0 || trim[0] > trim[1]) {
         alert("Incorrect trim data");
         console.error(trim);
@@ -93,8 +129,21 @@ $('#save_recording').on('click', function() {
         return;
     }
 
+    // Build the matching options, with the pose constraints
+    let options = [];
+    if($('#pose').val().length > 0)
+        options.push($('#pose').val());
+    if($('#extended').val().length > 0)
+        options.push('fingers=' + $('#extended').val());
+    options.push('sides=' + $('#sides').val());
+    options.push('plane=' + $('#plane').val());
+    if($('#spatial').val() === 'true')
+        options.push('spatial');
+    if($('#scale_invariant').val() === 'true')
+        options.push('any-size');
+
     // Send the create request
-    axios.get('/api/v1/template/create/' + encodeURIComponent(name) + '/' + trim[0] + '/' + trim[1])
+    axios.get('/api/v1/template/create/' + encodeURIComponent(name) + '/' + trim[0] + '/' + trim[1] + '/' + encodeURIComponent(options.join(',')))
         .then(function(response) {
             updateTemplateList();
             sendState(STATE_NORMAL);
@@ -152,6 +201,10 @@ $(document).ready(function() {
 
     // Build the trim slider
     buildTrimSlider();
+
+    // Update the list of sensors and their calibration, keep their status up to date
+    updateCalibration();
+    setInterval(updateSensorStatus, SENSOR_STATUS_INTERVAL);
 });
 
 /**
@@ -252,7 +305,7 @@ function fetchTemplates() {
     return new Promise(function(resolve, reject) {
         axios.get('/api/v1/template')
             .then(function(data) {
-                resolve(data.data.templates);
+                resolve(data.data);
             })
             .catch(reject);
     });
@@ -265,14 +318,29 @@ function fetchTemplates() {
 function updateTemplateList() {
     // TODO: catch and handle errors
     fetchTemplates()
-        .then(function(templates) {
+        .then(function(data) {
+            let templates = data.templates;
+            let postures = data.postures;
+
             // Put the template items into the list
             let list = $('.list-template');
             list.html("");
             templates.forEach(function(template) {
-                // Get the template id and name
+                // Get the template id, name and hand parts
                 let id = template.id;
                 let name = template.name;
+                let parts = template.fingers
+                    .map((finger) => finger.toLowerCase())
+                    .concat(template.palm ? ['palm'] : [])
+                    .concat(template.pose.pinch ? ['pinching'] : [])
+                    .concat(template.pose.grab ? ['grabbing'] : [])
+                    .concat(template.pose.extended !== null ? [template.pose.extended + ' extended'] : [])
+                    .concat(template.sides === 'only' ? [sidesName(template) + ' only'] : [])
+                    .concat(template.sides === 'mirror' ? ['mirrored'] : [])
+                    .concat(template.plane === 'fitted' ? ['any plane'] : [])
+                    .concat(template.spatial ? ['3D'] : [])
+                    .concat(template.scale_invariant ? ['any size'] : [])
+                    .join(', ');
                 $('<li class="list-group-item" />')
                     .text(name)
                     .append(
@@ -281,11 +349,27 @@ function updateTemplateList() {
                             .click(deleteTemplateCallback)
                     )
                     .append($('<span class="id"></span>').text('id: ' + id))
+                    .append($('<span class="parts"></span>').text(parts))
+                    .appendTo(list);
+            });
+
+            // Put the posture template items into the list
+            postures.forEach(function(posture) {
+                let id = posture.id;
+                $('<li class="list-group-item" />')
+                    .text(posture.name)
+                    .append(
+                        $('<button type="button" class="btn btn-sm btn-outline-danger" data-id="' + id + '" />')
+                            .text("X")
+                            .click(deleteTemplateCallback)
+                    )
+                    .append($('<span class="id"></span>').text('id: ' + id))
+                    .append($('<span class="parts"></span>').text('posture'))
                     .appendTo(list);
             });
 
             // Show a message if there are no templates
-            if(templates.length === 0)
+            if(templates.length === 0 && postures.length === 0)
                 list.html("<i>No templates configured, create one or add built-in templates using the button below!</i>");
         });
 }
@@ -391,6 +475,12 @@ var visualizerTimer = null;
  */
 var models = [];
 
+/**
+ * The interaction zone points are tracked in, and the latest sensed hand part positions.
+ */
+var zone = null;
+var positions = [];
+
 $('#toggle_visualize').on('click', function() {
     let visualizing = $(this).hasClass("btn-danger");
     setLiveVisualize(!visualizing);
@@ -425,13 +515,21 @@ function fetchVisualizer() {
             .then(function(response) {
                 // Get the models and detected gestures
                 models = response.data.models;
+                zone = response.data.zone;
+                positions = response.data.positions;
                 let detected = response.data.detected;
 
                 // Render a notification showing the detected gesture
                 if(detected !== undefined)
-                    detected.forEach((gesture) =>
+                    detected.forEach((detection) =>
                         $.notify({
-                            message: 'Detected: ' + gesture.name,
+                            message: 'Detected: ' + detection.template.name + ' ('
+                                + (detection.kind === 'posture'
+                                    ? (detection.side !== null ? detection.side.toLowerCase() + ' hand posture' : 'posture')
+                                    : detection.parts
+                                        .map(([part, side]) => (side !== null ? side.toLowerCase() + ' ' : '') + handPartName(part))
+                                        .join(', ')
+                                        + ', distance ' + detection.score.distance.toFixed(2)) + ')',
                         }, NOTIFY_SETTINGS)
                     );
 
@@ -501,26 +599,127 @@ function renderVisualizer(models) {
     models.forEach(function(model, i) {
         // Trim the points in the save state
         let points = model.trace.points;
-        if(state === STATE_SAVING)
+        let offset = 0;
+        if(state === STATE_SAVING) {
             points = points.slice(trim[0], trim[1]);
+            offset = trim[0];
+        }
+
+        // Get the stroke segment boundaries, relative to the trimmed points
+        let boundaries = [];
+        model.segments.forEach((segment) => {
+            boundaries.push(segment.start - offset);
+            if(segment.end !== null)
+                boundaries.push(segment.end - 1 - offset);
+        });
+
+        // Render the trace, dashed for palm traces
+        _renderVisualizerTrace(context, points, i, model.part === 'Palm', boundaries);
+    });
+
+    // Render the interaction zone in the corner, seen from the front and from the side
+    if(zone !== null) {
+        let top = 10;
+        let left = visualizer.width - 2 * ZONE_VIEW_SIZE - 20;
+        _renderVisualizerZone(context, left, top, 'x');
+        _renderVisualizerZone(context, left + ZONE_VIEW_SIZE + 10, top, 'z');
+    }
+}
+
+/**
+ * Render a view of the interaction zone and the sensed hand part positions on the visualizer.
+ *
+ * The vertical axis of the view is the Y axis, points inside the zone are drawn in green.
+ *
+ * @param {object} context The canvas 2D drawing context.
+ * @param {int} left The left position of the view.
+ * @param {int} top The top position of the view.
+ * @param {string} axis The horizontal axis of the view, `'x'` for the front or `'z'` for the side.
+ */
+function _renderVisualizerZone(context, left, top, axis) {
+    // Map coordinates in millimeters to view pixels, centered above the sensor
+    let scale = ZONE_VIEW_SIZE / ZONE_VIEW_RANGE;
+    let toX = (value) => left + ZONE_VIEW_SIZE / 2 + value * scale;
+    let toY = (value) => top + ZONE_VIEW_SIZE - value * scale;
+    let clampX = (value) => Math.min(Math.max(value, left), left + ZONE_VIEW_SIZE);
+
+    // Draw the view border and the axis label
+    context.setLineDash([]);
+    context.strokeStyle = '#BDBDBD';
+    context.fillStyle = '#757575';
+    context.strokeRect(left, top, ZONE_VIEW_SIZE, ZONE_VIEW_SIZE);
+    context.fillText(axis === 'x' ? 'front' : 'side', left + 4, top + 12);
+
+    // Shade the zone, a depth zone is only visible from the side
+    context.fillStyle = 'rgba(76, 175, 80, 0.2)';
+    if(zone.kind === 'box') {
+        let x1 = clampX(toX(zone.min[axis]));
+        let x2 = clampX(toX(zone.max[axis]));
+        let y1 = Math.max(toY(zone.max.y), top);
+        let y2 = Math.min(toY(zone.min.y), top + ZONE_VIEW_SIZE);
+        context.fillRect(x1, y1, x2 - x1, y2 - y1);
+    } else if(zone.kind === 'depth' && axis === 'z') {
+        let x = clampX(toX(zone.z));
+        context.fillRect(left, top, x - left, ZONE_VIEW_SIZE);
+    } else {
+        context.fillRect(left, top, ZONE_VIEW_SIZE, ZONE_VIEW_SIZE);
+    }
 
-        // Render the trace
-        _renderVisualizerTrace(context, points, i);
+    // Draw the sensed positions
+    positions.forEach((position) => {
+        let x = toX(position.point[axis]);
+        let y = toY(position.point.y);
+        if(x < left || x > left + ZONE_VIEW_SIZE || y < top || y > top + ZONE_VIEW_SIZE)
+            return;
+        context.fillStyle = position.inside ? '#4CAF50' : '#9E9E9E';
+        context.beginPath();
+        context.arc(x, y, position.part === 'Palm' ? 3.5 : 2, 0, 2 * Math.PI);
+        context.fill();
     });
 }
 
+/**
+ * Get a readable name for the given hand part, as serialized by the server.
+ *
+ * @param {string|object} part The hand part, `'Palm'` or `{ Finger: 'Index' }`.
+ * @return {string} The hand part name.
+ */
+function handPartName(part) {
+    if(part === 'Palm')
+        return 'palm';
+    return part.Finger.toLowerCase() + ' finger';
+}
+
+/**
+ * Get a readable name for the hand sides the given template was recorded with.
+ *
+ * @param {object} template The template, as serialized by the server.
+ * @return {string} The hand sides, such as `'left hand'`, or `'recorded hand'` if unknown.
+ */
+function sidesName(template) {
+    let sides = template.model.traces
+        .filter((trace) => trace.side !== null)
+        .map((trace) => trace.side.toLowerCase());
+    if(sides.length === 0)
+        return 'recorded hand';
+    return sides.filter((side, i) => sides.indexOf(side) === i).join(' and ') + ' hand';
+}
+
 /**
  * Render a trace based on the given set of points on the visualizer.
  *
  * @param {object} context The canvas 2D drawing context.
  * @param {object[]} points The list of points in a trace to draw.
  * @param {int} i The index of this trace, used to determine what color to use.
+ * @param {bool} dashed Whether to draw the trace with a dashed line.
+ * @param {int[]} boundaries Indices of points at stroke segment boundaries, to mark.
  */
-function _renderVisualizerTrace(context, points, i) {
+function _renderVisualizerTrace(context, points, i, dashed, boundaries) {
     // Determine the color to use, and set it
     let color = COLORS[(i || 0) % COLORS.length];
     context.strokeStyle = color;
     context.fillStyle = color;
+    context.setLineDash(dashed ? [4, 4] : []);
 
     // Cummulative coordinate and rotation values
     let last_x = 400;
@@ -560,4 +759,158 @@ function _renderVisualizerTrace(context, points, i) {
         context.arc(points[i].x, points[i].y, 1.5, 0, 2 * Math.PI);
         context.fill();
     }
+
+    // Mark the stroke segment boundaries
+    context.setLineDash([]);
+    boundaries
+        .filter((i) => i >= 0 && i < points.length)
+        .forEach((i) => {
+            context.beginPath();
+            context.arc(points[i].x, points[i].y, 5, 0, 2 * Math.PI);
+            context.stroke();
+        });
+}
+
+
+
+
+$('#capture_calibration_pose').on('click', function() {
+    sendCalibration('/api/v1/calibration/wizard/capture')
+        .then(function(data) {
+            // Notify when the last pose was captured successfully
+            if(data.error === null && data.wizard === null)
+                $.notify({
+                    message: 'Sensor calibrated',
+                }, NOTIFY_SETTINGS);
+        });
+});
+
+$('#cancel_calibration_wizard').on('click', function() {
+    sendCalibration('/api/v1/calibration/wizard/cancel');
+});
+
+$('#add_sensor').on('submit', function(event) {
+    event.preventDefault();
+
+    // Add a network sensor receiving on the given address
+    let kind = $('#sensor_kind').val();
+    let address = $('#sensor_address').val().trim();
+    if(address.length === 0)
+        return;
+    sendCalibration(
+        '/api/v1/sensor/add/' + kind + '/' + encodeURIComponent(address),
+        'Adding sensor failed'
+    );
+});
+
+/**
+ * Fetch the sensor calibration state from the server, then update the DOM.
+ */
+function updateCalibration() {
+    sendCalibration('/api/v1/calibration');
+}
+
+/**
+ * Send a calibration or sensor request to the given URL, and render the
+ * calibration state in the response.
+ *
+ * If the response holds an error, it is shown to the user.
+ *
+ * @param {string} url The calibration or sensor API URL to request.
+ * @param {string} failure The message to show along with errors.
+ * @return {Promise} A promise for the response data.
+ */
+function sendCalibration(url, failure = 'Calibration failed') {
+    return new Promise(function(resolve, reject) {
+        axios.get(url)
+            .then(function(response) {
+                let data = response.data;
+                if(data.error !== null)
+                    alert(failure + ': ' + data.error);
+                renderCalibration(data);
+                resolve(data);
+            })
+            .catch(function(err) {
+                console.error(err);
+                alert('An error occurred while requesting sensor calibration: ' + err);
+                reject(err);
+            });
+    });
+}
+
+/**
+ * Render the given sensor calibration state.
+ *
+ * @param {object} data The calibration state, with a list of sensors and the
+ *     running calibration wizard.
+ */
+function renderCalibration(data) {
+    // Put the sensor items into the list
+    let list = $('.list-sensor');
+    list.html("");
+    data.sensors.forEach(function(sensor) {
+        $('<li class="list-group-item" data-id="' + sensor.id + '" />')
+            .text('Sensor ' + sensor.id)
+            .append($('<span class="name"></span>').text(sensor.name))
+            .append(
+                $('<button type="button" class="btn btn-sm btn-outline-danger" />')
+                    .text("Reset")
+                    .click(() => sendCalibration('/api/v1/calibration/' + sensor.id + '/reset'))
+            )
+            .append(
+                $('<button type="button" class="btn btn-sm btn-outline-primary" />')
+                    .text("Calibrate")
+                    .click(() => sendCalibration('/api/v1/calibration/' + sensor.id + '/wizard'))
+            )
+            .append(
+                $('<button type="button" class="btn btn-sm btn-outline-secondary" />')
+                    .text("Remove")
+                    .click(() => sendCalibration(
+                        '/api/v1/sensor/' + sensor.id + '/remove',
+                        'Removing sensor failed'
+                    ))
+            )
+            .append($('<div class="status"></div>'))
+            .appendTo(list);
+    });
+
+    // Show a message if there are no sensors
+    if(data.sensors.length === 0)
+        list.html("<i>No sensors running.</i>");
+
+    // Show the calibration wizard if running
+    let panel = $('.calibration-wizard');
+    let wizard = data.wizard;
+    panel.css('display', wizard !== null ? 'block' : 'none');
+    if(wizard !== null) {
+        panel.find('.sensor').text(wizard.sensor);
+        panel.find('.step').text((wizard.step + 1) + '/' + wizard.steps);
+        panel.find('.instruction').text(wizard.instruction);
+    }
+}
+
+/**
+ * Fetch the status of all sensors from the server, then update the status
+ * shown in the list of sensors.
+ */
+function updateSensorStatus() {
+    axios.get('/api/v1/sensor')
+        .then(function(response) {
+            response.data.sensors.forEach(function(sensor) {
+                // Describe the sensor status
+                let status = sensor.connected ? 'Connected' : 'Disconnected';
+                status += ' | ' + sensor.frame_rate.toFixed(0) + ' fps';
+                status += ' | ' + sensor.hands + ' hand(s) in view';
+                status += ' | ' + sensor.frames + ' frames, ' + sensor.frames_dropped + ' dropped';
+                if(sensor.last_frame_age !== null)
+                    status += ' | last frame ' + sensor.last_frame_age + ' ms ago';
+
+                $('.list-sensor .list-group-item[data-id="' + sensor.id + '"] .status')
+                    .text(status)
+                    .toggleClass('disconnected', !sensor.connected);
+            });
+        })
+        .catch(function(error) {
+            console.log(error);
+        });
 }
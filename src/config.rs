@@ -12,6 +12,164 @@ pub mod trace {
     ///
     /// If traces get longer than this maximum, they will be trimmed automatically.
     pub const MAX_POINTS: usize = 2048;
+
+    /// The maximum age of points in a trace in microseconds, relative to the newest point.
+    ///
+    /// If traces get older than this maximum, they will be trimmed automatically. Traces aren't
+    /// trimmed by age while recording, as a template may take any time to record.
+    pub const MAX_AGE: i64 = 10_000_000;
+
+    /// The minimum ratio of the length of an edge projected onto the drawing plane to its real
+    /// length, for its heading to be used when computing the turn between edges.
+    ///
+    /// The heading of an edge pointing almost straight out of the drawing plane is mostly noise,
+    /// so no turn is computed for it. Its pitch still describes it.
+    pub const MIN_PLANAR_RATIO: f64 = 0.25;
+}
+
+/// Fragment related configuration.
+pub mod fragment {
+    /// The time in microseconds after which a hand that isn't seen by its sensor anymore is
+    /// dropped, along with its traces.
+    ///
+    /// This allows a hand to briefly drop out of view without losing its traces.
+    pub const HAND_EXPIRY: i64 = 500_000;
+
+    /// The time in microseconds after which a fragment of a hand part that isn't seen anymore is
+    /// dropped, such as a finger hidden behind the hand.
+    pub const FRAGMENT_EXPIRY: i64 = 250_000;
+}
+
+/// Hand pose related configuration.
+pub mod pose {
+    /// The minimum pinch strength, from `0` to `1`, for a hand to be considered pinching.
+    pub const PINCH_THRESHOLD: f64 = 0.8;
+
+    /// The minimum grab strength, from `0` to `1`, for a hand to be considered grabbing.
+    pub const GRAB_THRESHOLD: f64 = 0.8;
+}
+
+/// Network sensor source related configuration.
+pub mod network {
+    /// The maximum time in milliseconds network sources wait for data, before checking whether
+    /// they should stop.
+    ///
+    /// This bounds how long stopping a network source takes.
+    pub const POLL_INTERVAL: u64 = 100;
+}
+
+/// Frame replay related configuration.
+pub mod replay {
+    /// The maximum time in milliseconds a replay sleeps for while waiting for the next frame,
+    /// before checking whether it should stop.
+    ///
+    /// This bounds how long stopping a replay takes, when recorded frames are far apart.
+    pub const POLL_INTERVAL: u64 = 100;
+}
+
+/// Interaction zone related configuration.
+///
+/// These are the defaults for zones, bounds can be given per zone.
+pub mod zone {
+    /// The corner of the default box zone with the lowest coordinates, in millimeters.
+    pub const BOX_MIN: [f64; 3] = [-150.0, 100.0, -150.0];
+
+    /// The corner of the default box zone with the highest coordinates, in millimeters.
+    pub const BOX_MAX: [f64; 3] = [150.0, 350.0, 100.0];
+
+    /// The default depth threshold in millimeters of a depth zone, along the Z axis.
+    ///
+    /// Points closer to the user than this aren't tracked.
+    pub const DEPTH: f64 = 0.0;
+}
+
+/// Noise filter related configuration.
+///
+/// These are the defaults for filter profiles, parameters can be tuned per profile.
+pub mod filter {
+    /// The smoothing factor of the exponential moving average filter, from `0` to `1`.
+    pub const EMA_ALPHA: f64 = 0.5;
+
+    /// The minimum cutoff frequency in hertz of the One-Euro filter, used when not moving.
+    pub const ONE_EURO_MIN_CUTOFF: f64 = 1.0;
+
+    /// The factor the cutoff frequency of the One-Euro filter increases by with speed.
+    pub const ONE_EURO_BETA: f64 = 0.02;
+
+    /// The cutoff frequency in hertz the One-Euro filter smooths the speed with.
+    pub const ONE_EURO_D_CUTOFF: f64 = 1.0;
+
+    /// The process noise of the Kalman filter, as acceleration variance in squared millimeters per
+    /// squared second.
+    pub const KALMAN_PROCESS_NOISE: f64 = 1_000_000.0;
+
+    /// The measurement noise of the Kalman filter, as position variance in squared millimeters.
+    pub const KALMAN_MEASUREMENT_NOISE: f64 = 1.0;
+}
+
+/// Drawing plane related configuration.
+pub mod plane {
+    /// The direction the user views gestures from, that normals of drawing planes are flipped
+    /// towards.
+    ///
+    /// This faces the user for planes in front of them, and faces up for planes flat over a desk.
+    pub const VIEW_DIRECTION: [f64; 3] = [0.0, 1.0, 1.0];
+
+    /// The time window in microseconds of recent points a drawing plane is fitted through,
+    /// relative to the newest point.
+    ///
+    /// When the drawing plane changes, the fitted trace is rebuilt from the points in this window
+    /// only.
+    pub const FIT_WINDOW: i64 = 1_500_000;
+
+    /// The number of points after which the drawing plane of a trace is fitted again.
+    pub const FIT_INTERVAL: usize = 5;
+
+    /// The minimum number of points to fit a drawing plane through.
+    pub const MIN_POINTS: usize = 10;
+
+    /// The minimum variance in squared millimeters points must have along both axes of a plane
+    /// for it to be fitted.
+    ///
+    /// This prevents fitting a plane through a straight line, or a finger that barely moves.
+    pub const MIN_VARIANCE: f64 = 25.0;
+
+    /// The minimum factor the variance along both axes of a plane must exceed the variance
+    /// perpendicular to it by, for it to be fitted.
+    pub const MIN_PLANARITY: f64 = 4.0;
+
+    /// The minimum angle in radians a newly fitted drawing plane must differ from the current one
+    /// by, for a trace to be projected onto it again.
+    ///
+    /// Reprojecting rebuilds the fitted rotational trace, so small changes are ignored.
+    pub const REFIT_ANGLE: f64 = 0.15;
+}
+
+/// Stroke segmentation related configuration.
+pub mod segmentation {
+    /// The time window in microseconds the finger velocity is measured over, relative to the
+    /// newest point.
+    pub const VELOCITY_WINDOW: i64 = 100_000;
+
+    /// The velocity in millimeters per second the finger must speed up to, to start a stroke.
+    pub const START_VELOCITY: f64 = 150.0;
+
+    /// The velocity in millimeters per second the finger must slow down to, to pause a stroke.
+    pub const END_VELOCITY: f64 = 50.0;
+
+    /// The time in microseconds a stroke must be paused for, for it to end.
+    pub const DWELL_TIME: i64 = 150_000;
+
+    /// The minimum number of rotational points a stroke must have to be matched.
+    ///
+    /// Shorter strokes are considered to be unintended movement, and are dropped.
+    pub const MIN_POINTS: usize = 5;
+}
+
+/// Posture related configuration.
+pub mod posture {
+    /// The default time in microseconds a posture must be held for to be detected.
+    pub const HOLD_TIME: i64 = 1_000_000;
 }
 
 /// Recognition related configuration.
@@ -24,6 +182,13 @@ pub mod recognition {
     /// valid.
     pub const MARGIN: f64 = 0.2;
 
+    /// The maximum margin the pitch of two points may differ by for them to be considered a
+    /// match, when comparing spatial traces.
+    ///
+    /// This only applies to templates matching the full 3D shape of a trace, other templates
+    /// ignore the pitch of points.
+    pub const PITCH_MARGIN: f64 = 0.35;
+
     /// The margin start interrupting comparison searches at.
     ///
     /// When comparing two traces with rotations, for each point in a trace multiple points in the
@@ -63,6 +228,12 @@ pub mod recognition {
     /// history because of a recognized gesture.
     pub const KEEP_POINTS: usize = 2;
 
+    /// The time window in microseconds all traces of a multi-trace model must match within.
+    ///
+    /// Each trace of a model with multiple traces is matched separately. The model is only
+    /// detected if the traces matched at their expected relative timing, within this window.
+    pub const MULTI_TRACE_WINDOW: i64 = 500_000;
+
     // TODO: used for the old recognition logic, consider what to keep
     // /// The maximum allowed angle difference threshold in radians of the cumulative angles
     // /// between a trace and a trace template.
@@ -83,8 +254,68 @@ pub mod recognition {
     // pub const GROUP_DIFF_MAX: f64 = 1.5;
 }
 
+/// Scale invariant matching related configuration.
+pub mod scale {
+    /// The number of points traces of scale invariant templates are resampled to.
+    pub const POINTS: usize = 40;
+
+    /// The minimum number of rotational points a trace must have to be matched against scale
+    /// invariant templates.
+    ///
+    /// Traces are compared at their end over windows of increasing length, starting at this
+    /// length.
+    pub const MIN_WINDOW: usize = 10;
+
+    /// The factor the length of each window compared against scale invariant templates grows by.
+    ///
+    /// A smaller factor tries more sizes, at a higher cost.
+    pub const WINDOW_GROWTH: f64 = 1.15;
+}
+
+/// Template recording related configuration.
+pub mod recording {
+    /// The minimum length of a hand's trace relative to the longest trace, for the hand to be
+    /// recorded in a template.
+    ///
+    /// Hands with a shorter trace are considered to be idle while recording, and are left out.
+    pub const ACTIVE_TRACE_RATIO: f64 = 0.5;
+}
+
+/// Sensor calibration related configuration.
+pub mod calibration {
+    /// The name of the calibrations file.
+    pub const CALIBRATIONS_FILE: &str = "calibrations.json";
+
+    /// The distance in millimeters the user is guided to move a finger tip by, when calibrating.
+    pub const GUIDE_DISTANCE: f64 = 100.0;
+
+    /// The position in the shared coordinate space the calibration origin is mapped onto.
+    ///
+    /// This is about the height above a sensor on a desk at which gestures are drawn.
+    pub const ORIGIN: [f64; 3] = [0.0, 200.0, 0.0];
+
+    /// The number of most recent finger tip positions that are averaged when capturing a
+    /// calibration pose.
+    pub const CAPTURE_SAMPLES: usize = 30;
+}
+
+/// Synthetic gesture related configuration.
+pub mod synth {
+    /// The number of frames per second synthetic strokes are generated at.
+    pub const FRAME_RATE: f64 = 100.0;
+
+    /// The default speed in millimeters per second synthetic strokes are drawn at.
+    pub const SPEED: f64 = 250.0;
+
+    /// The distance in millimeters the palm is behind the finger tip in synthetic frames.
+    pub const PALM_DISTANCE: f64 = 70.0;
+}
+
 /// Template related configuration.
 pub mod template {
     /// The name of the templates file.
     pub const TEMPLATES_FILE: &str = "templates.json";
+
+    /// The name of the posture templates file.
+    pub const POSTURES_FILE: &str = "postures.json";
 }
@@ -1,12 +1,21 @@
-use std::{io::Result, sync::Arc};
+use std::{
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
 use clap::ArgMatches;
 use webbrowser;
 
 use fragment::FragmentManager;
 use gesture::GestureController;
-use sensor::SensorController;
-use store::template::TemplateStore;
+#[cfg(feature = "leap")]
+use sensor::LeapMotionSource;
+use sensor::{
+    Calibrator, DemoSource, FrameRecorder, ReplaySource, SensorController, SensorSource,
+    StatusMonitor, UdpSource, WebSocketSource,
+};
+use store::{template::TemplateStore, CalibrationStore};
 #[cfg(feature = "web")]
 use web::server::Server;
 
@@ -18,8 +27,9 @@ pub struct Core {
 
     /// The sensor controller, handling the sensor data
     ///
-    /// This produces 3D point traces.
-    sensor_controller: SensorController,
+    /// This produces 3D point traces. It is shared with the web server, to add and remove sensors
+    /// at runtime.
+    sensor_controller: Arc<Mutex<SensorController>>,
 
     /// The fragment manager, keeping track of trace fragments.
     fragment_manager: Arc<FragmentManager>,
@@ -34,6 +44,11 @@ pub struct Core {
     /// This is used by the gesture controller to match new against.
     store: Arc<TemplateStore>,
 
+    /// The sensor calibration store
+    ///
+    /// This is used by the calibrator to restore and save sensor calibrations.
+    calibration_store: Arc<CalibrationStore>,
+
     /// The web server
     ///
     /// This is used to launch a web based configuration window for the user.
@@ -50,27 +65,63 @@ impl Core {
 
         // Build components in order, depending on each other
         let store = Arc::new(TemplateStore::new());
-        let gesture_controller = Arc::new(GestureController::new(store.clone()));
+        let gesture_controller = Arc::new(GestureController::new(
+            store.clone(),
+            matches.is_present("segment"),
+            matches
+                .value_of("filter")
+                .map(|filter| filter.parse().expect("invalid filter"))
+                .unwrap_or_default(),
+            matches
+                .value_of("zone")
+                .map(|zone| zone.parse().expect("invalid zone"))
+                .unwrap_or_default(),
+        ));
         let fragment_manager = Arc::new(FragmentManager::new(gesture_controller.clone()));
 
+        let calibration_store = Arc::new(CalibrationStore::new());
+        let mount = matches
+            .value_of("mount")
+            .map(|mount| mount.parse().expect("invalid mount"))
+            .unwrap_or_default();
+        let calibrator = Arc::new(Calibrator::new(calibration_store.clone(), mount));
+        let monitor = Arc::new(StatusMonitor::new());
+
+        let sensor_controller = Arc::new(Mutex::new(SensorController::new(
+            fragment_manager.clone(),
+            calibrator.clone(),
+            monitor.clone(),
+        )));
+
         // TODO: this is temporary
         gesture_controller.set_fragment_manager(fragment_manager.clone());
 
         Core {
             matches,
-            sensor_controller: SensorController::new(fragment_manager.clone()),
+            sensor_controller: sensor_controller.clone(),
             fragment_manager,
             gesture_controller: gesture_controller.clone(),
             store: store.clone(),
+            calibration_store,
             #[cfg(feature = "web")]
-            server: Server::new(gesture_controller, store),
+            server: Server::new(
+                gesture_controller,
+                store,
+                sensor_controller,
+                calibrator,
+                monitor,
+            ),
         }
     }
 
     /// Start the core.
     pub fn start(&mut self) -> Result<()> {
-        // Load the templates
+        // Load the templates and sensor calibrations
         self.store.load()?;
+        self.calibration_store.load()?;
+
+        // Start the sensors
+        self.start_sensors()?;
 
         #[cfg(feature = "web")]
         {
@@ -87,9 +138,112 @@ impl Core {
             self.server.start();
         }
 
+        // Without the web server, keep running until the sensors stop
+        // Nothing else adds or removes sensors then, so the controller stays locked
+        #[cfg(not(feature = "web"))]
+        self.sensor_controller
+            .lock()
+            .expect("failed to lock sensor controller")
+            .wait();
+
+        Ok(())
+    }
+
+    /// Start the sensors.
+    ///
+    /// If requested through the CLI arguments, calibrated frames of the sensor are recorded to a
+    /// file. Recording fails with multiple sensor sources, as recorded frames don't tell which
+    /// sensor produced them.
+    fn start_sensors(&mut self) -> Result<()> {
+        // Select the sensor sources
+        let sources = self.sensor_sources();
+        if sources.is_empty() {
+            eprintln!("No sensor source available, not tracking any hands");
+            return Ok(());
+        }
+
+        // Create the frame recorder if requested
+        let mut recorder = match self.matches.value_of("record") {
+            Some(_) if sources.len() > 1 => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "can't record frames of multiple sensor sources, use a single source",
+                ))
+            }
+            Some(path) => {
+                println!("Recording sensor frames to {}...", path);
+                Some(FrameRecorder::create(Path::new(path))?)
+            }
+            None => None,
+        };
+
+        let mut sensor_controller = self
+            .sensor_controller
+            .lock()
+            .expect("failed to lock sensor controller");
+        for source in sources {
+            sensor_controller.add(source, recorder.take())?;
+        }
+
         Ok(())
     }
 
+    /// Select the sensor sources to use.
+    ///
+    /// A frame file is replayed or demo strokes are drawn if requested through the CLI arguments.
+    /// Otherwise points are received from all requested network sources, and the default sensor
+    /// source is used if no network source is requested or if explicitly requested.
+    fn sensor_sources(&self) -> Vec<Box<dyn SensorSource>> {
+        if self.matches.is_present("demo") {
+            println!("Drawing demo strokes...");
+            return vec![Box::new(DemoSource::new())];
+        }
+
+        if let Some(path) = self.matches.value_of("replay") {
+            let speed = self
+                .matches
+                .value_of("replay-speed")
+                .map(|speed| speed.parse().expect("invalid replay speed"))
+                .unwrap_or_default();
+            println!("Replaying sensor frames from {}...", path);
+            return vec![Box::new(ReplaySource::new(path.into(), speed))];
+        }
+
+        let mut sources: Vec<Box<dyn SensorSource>> = Vec::new();
+
+        for address in self.matches.values_of("udp").into_iter().flatten() {
+            let address = address.parse().expect("invalid UDP address");
+            println!("Receiving points over UDP on {}...", address);
+            sources.push(Box::new(UdpSource::new(address)));
+        }
+
+        for address in self.matches.values_of("websocket").into_iter().flatten() {
+            let address = address.parse().expect("invalid WebSocket address");
+            println!("Receiving points over WebSocket on {}...", address);
+            sources.push(Box::new(WebSocketSource::new(address)));
+        }
+
+        if sources.is_empty() || self.matches.is_present("leap") {
+            sources.extend(Self::default_sensor_source());
+        }
+
+        sources
+    }
+
+    /// The default sensor source, this is the Leap Motion sensor.
+    #[cfg(feature = "leap")]
+    fn default_sensor_source() -> Option<Box<dyn SensorSource>> {
+        Some(Box::new(LeapMotionSource::new()))
+    }
+
+    /// The default sensor source.
+    ///
+    /// No sensor source is available as the `leap` feature isn't enabled, so `None` is returned.
+    #[cfg(not(feature = "leap"))]
+    fn default_sensor_source() -> Option<Box<dyn SensorSource>> {
+        None
+    }
+
     /// Stop the core.
     pub fn stop(&mut self) -> Result<()> {
         // Save the templates
@@ -0,0 +1,39 @@
+use super::{from_array, to_array, Filter};
+use types::Point3;
+
+/// An exponential moving average filter.
+///
+/// Each filtered point moves towards the sensed point by the smoothing factor `alpha`. A lower
+/// factor smooths more, but lags more behind.
+#[derive(Clone, Debug)]
+pub struct EmaFilter {
+    /// The smoothing factor, from `0` to `1`.
+    alpha: f64,
+
+    /// The last filtered point.
+    last: Option<[f64; 3]>,
+}
+
+impl EmaFilter {
+    /// Construct a new filter with the given smoothing factor `alpha`, from `0` to `1`.
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha, last: None }
+    }
+}
+
+impl Filter for EmaFilter {
+    fn filter(&mut self, point: Point3, _timestamp: i64) -> Point3 {
+        let mut coords = to_array(point);
+        if let Some(last) = self.last {
+            for (coord, last) in coords.iter_mut().zip(&last) {
+                *coord = last + self.alpha * (*coord - last);
+            }
+        }
+        self.last = Some(coords);
+        from_array(coords)
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
@@ -0,0 +1,114 @@
+use super::{from_array, to_array, Filter};
+use types::Point3;
+
+/// A Kalman filter, modelling movement with a constant velocity along each axis.
+///
+/// Changes in velocity are considered process noise, jitter in sensed points measurement noise.
+/// A higher process noise relative to the measurement noise follows the sensed points more
+/// closely, a lower one smooths more.
+#[derive(Clone, Debug)]
+pub struct KalmanFilter {
+    /// The process noise, as acceleration variance in squared millimeters per squared second.
+    process_noise: f64,
+
+    /// The measurement noise, as position variance in squared millimeters.
+    measurement_noise: f64,
+
+    /// The state of each axis, and the timestamp of the last point.
+    state: Option<([Axis; 3], i64)>,
+}
+
+impl KalmanFilter {
+    /// Construct a new filter with the given process and measurement noise.
+    pub fn new(process_noise: f64, measurement_noise: f64) -> Self {
+        Self {
+            process_noise,
+            measurement_noise,
+            state: None,
+        }
+    }
+}
+
+impl Filter for KalmanFilter {
+    fn filter(&mut self, point: Point3, timestamp: i64) -> Point3 {
+        let coords = to_array(point);
+
+        let (mut axes, last_timestamp) = match self.state {
+            Some(state) => state,
+            None => {
+                let mut axes = [Axis::default(); 3];
+                for (axis, coord) in axes.iter_mut().zip(&coords) {
+                    *axis = Axis::new(*coord, self.measurement_noise);
+                }
+                self.state = Some((axes, timestamp));
+                return point;
+            }
+        };
+
+        let dt = (timestamp - last_timestamp).max(0) as f64 / 1_000_000.0;
+        for (axis, coord) in axes.iter_mut().zip(&coords) {
+            axis.predict(dt, self.process_noise);
+            axis.update(*coord, self.measurement_noise);
+        }
+
+        self.state = Some((axes, timestamp));
+        from_array([axes[0].position, axes[1].position, axes[2].position])
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// The Kalman filter state of a single axis, with a position and velocity.
+#[derive(Copy, Clone, Debug, Default)]
+struct Axis {
+    /// The estimated position.
+    position: f64,
+
+    /// The estimated velocity.
+    velocity: f64,
+
+    /// The estimate covariance matrix, as rows.
+    covariance: [[f64; 2]; 2],
+}
+
+impl Axis {
+    /// Start estimating at the given `position` with the given variance, without velocity.
+    fn new(position: f64, variance: f64) -> Self {
+        Self {
+            position,
+            velocity: 0.0,
+            covariance: [[variance, 0.0], [0.0, 0.0]],
+        }
+    }
+
+    /// Predict the state the given `dt` seconds ahead, with the given process noise.
+    fn predict(&mut self, dt: f64, noise: f64) {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        self.position += self.velocity * dt;
+        self.covariance = [
+            [
+                p00 + dt * (p10 + p01) + dt * dt * p11 + noise * dt.powi(4) / 4.0,
+                p01 + dt * p11 + noise * dt.powi(3) / 2.0,
+            ],
+            [
+                p10 + dt * p11 + noise * dt.powi(3) / 2.0,
+                p11 + noise * dt * dt,
+            ],
+        ];
+    }
+
+    /// Update the state with the given measured `position`, with the given measurement noise.
+    fn update(&mut self, position: f64, noise: f64) {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let innovation = position - self.position;
+        let (k0, k1) = (p00 / (p00 + noise), p10 / (p00 + noise));
+        self.position += k0 * innovation;
+        self.velocity += k1 * innovation;
+        self.covariance = [
+            [(1.0 - k0) * p00, (1.0 - k0) * p01],
+            [p10 - k1 * p00, p11 - k1 * p01],
+        ];
+    }
+}
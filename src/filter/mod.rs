@@ -0,0 +1,318 @@
+//! Noise filtering of sensed points.
+//!
+//! Sensors report slightly jittery positions, which shows up as angle noise in rotational
+//! traces. Filters smooth the points of a trace before it is sampled, at the cost of some lag.
+//!
+//! The filter that is used, and how it is tuned, is selected through a `FilterProfile`.
+
+pub mod ema;
+pub mod kalman;
+pub mod one_euro;
+
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+
+use config::filter::{
+    EMA_ALPHA, KALMAN_MEASUREMENT_NOISE, KALMAN_PROCESS_NOISE, ONE_EURO_BETA, ONE_EURO_D_CUTOFF,
+    ONE_EURO_MIN_CUTOFF,
+};
+use types::Point3;
+
+/// Re-exports
+pub use self::ema::EmaFilter;
+pub use self::kalman::KalmanFilter;
+pub use self::one_euro::OneEuroFilter;
+
+/// A noise filter for a stream of sensed points.
+pub trait Filter: Debug + Send {
+    /// Filter the given `point`, sensed at the given `timestamp` in microseconds.
+    ///
+    /// The filtered point is returned.
+    fn filter(&mut self, point: Point3, timestamp: i64) -> Point3;
+
+    /// Reset the filter, to start filtering a new stream of points.
+    fn reset(&mut self);
+}
+
+/// A filter profile, selecting the filter to use and how it is tuned.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FilterProfile {
+    /// No filtering.
+    None,
+
+    /// An exponential moving average filter, see `EmaFilter`.
+    Ema { alpha: f64 },
+
+    /// A One-Euro filter, see `OneEuroFilter`.
+    OneEuro {
+        min_cutoff: f64,
+        beta: f64,
+        d_cutoff: f64,
+    },
+
+    /// A Kalman filter, see `KalmanFilter`.
+    Kalman {
+        process_noise: f64,
+        measurement_noise: f64,
+    },
+}
+
+impl FilterProfile {
+    /// Build a new filter as described by this profile.
+    ///
+    /// If no filtering is used, `None` is returned.
+    pub fn build(&self) -> Option<Box<dyn Filter>> {
+        match *self {
+            FilterProfile::None => None,
+            FilterProfile::Ema { alpha } => Some(Box::new(EmaFilter::new(alpha))),
+            FilterProfile::OneEuro {
+                min_cutoff,
+                beta,
+                d_cutoff,
+            } => Some(Box::new(OneEuroFilter::new(min_cutoff, beta, d_cutoff))),
+            FilterProfile::Kalman {
+                process_noise,
+                measurement_noise,
+            } => Some(Box::new(KalmanFilter::new(
+                process_noise,
+                measurement_noise,
+            ))),
+        }
+    }
+}
+
+impl Default for FilterProfile {
+    fn default() -> Self {
+        FilterProfile::None
+    }
+}
+
+impl Display for FilterProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterProfile::None => write!(f, "none"),
+            FilterProfile::Ema { alpha } => write!(f, "ema:alpha={}", alpha),
+            FilterProfile::OneEuro {
+                min_cutoff,
+                beta,
+                d_cutoff,
+            } => write!(
+                f,
+                "one-euro:min-cutoff={},beta={},d-cutoff={}",
+                min_cutoff, beta, d_cutoff,
+            ),
+            FilterProfile::Kalman {
+                process_noise,
+                measurement_noise,
+            } => write!(
+                f,
+                "kalman:process-noise={},measurement-noise={}",
+                process_noise, measurement_noise,
+            ),
+        }
+    }
+}
+
+impl FromStr for FilterProfile {
+    type Err = String;
+
+    /// Parse a filter profile, being `none`, `ema`, `one-euro` or `kalman`, optionally followed by
+    /// a colon and a comma separated list of parameters to tune.
+    ///
+    /// For example: `one-euro:min-cutoff=1.5,beta=0.02`. Parameters that aren't given use their
+    /// default from `config::filter`.
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        let mut parts = profile.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let params = parts.next().unwrap_or("");
+
+        let mut result = match kind {
+            "none" => FilterProfile::None,
+            "ema" => FilterProfile::Ema { alpha: EMA_ALPHA },
+            "one-euro" => FilterProfile::OneEuro {
+                min_cutoff: ONE_EURO_MIN_CUTOFF,
+                beta: ONE_EURO_BETA,
+                d_cutoff: ONE_EURO_D_CUTOFF,
+            },
+            "kalman" => FilterProfile::Kalman {
+                process_noise: KALMAN_PROCESS_NOISE,
+                measurement_noise: KALMAN_MEASUREMENT_NOISE,
+            },
+            _ => {
+                return Err(format!(
+                    "invalid filter '{}', must be 'none', 'ema', 'one-euro' or 'kalman'",
+                    kind,
+                ))
+            }
+        };
+
+        // Tune the parameters
+        for param in params.split(',').filter(|param| !param.is_empty()) {
+            let mut pair = param.splitn(2, '=');
+            let name = pair.next().unwrap_or("");
+            let value: f64 = pair
+                .next()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("invalid filter parameter '{}'", param))?;
+
+            let target = match (&mut result, name) {
+                (FilterProfile::Ema { alpha }, "alpha") => alpha,
+                (FilterProfile::OneEuro { min_cutoff, .. }, "min-cutoff") => min_cutoff,
+                (FilterProfile::OneEuro { beta, .. }, "beta") => beta,
+                (FilterProfile::OneEuro { d_cutoff, .. }, "d-cutoff") => d_cutoff,
+                (FilterProfile::Kalman { process_noise, .. }, "process-noise") => process_noise,
+                (
+                    FilterProfile::Kalman {
+                        measurement_noise, ..
+                    },
+                    "measurement-noise",
+                ) => measurement_noise,
+                _ => {
+                    return Err(format!(
+                        "unknown parameter '{}' for filter '{}'",
+                        name, kind
+                    ))
+                }
+            };
+            *target = value;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Get the coordinates of the given point as array.
+fn to_array(point: Point3) -> [f64; 3] {
+    [point.x, point.y, point.z]
+}
+
+/// Get a point from the given array of coordinates.
+fn from_array(coords: [f64; 3]) -> Point3 {
+    Point3::new(coords[0], coords[1], coords[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{prng::XorShiftRng, Rng, SeedableRng};
+
+    use super::*;
+    use config::synth::FRAME_RATE;
+    use synth::{Shape, Stroke};
+    use types::{PointTrace, TimedPoint3};
+
+    /// Draw a clean and a noisy version of the given stroke, as timed points.
+    fn noisy_stroke(stroke: Stroke) -> (Vec<Point3>, Vec<TimedPoint3>) {
+        let mut rng = XorShiftRng::from_seed([3; 16]);
+        let interval = (1_000_000.0 / FRAME_RATE) as i64;
+        let clean = stroke.points(Point3::zero(), &mut rng);
+        let noisy = clean
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                TimedPoint3::new(
+                    Point3::new(
+                        p.x + rng.gen_range(-1.5, 1.5),
+                        p.y + rng.gen_range(-1.5, 1.5),
+                        p.z + rng.gen_range(-1.5, 1.5),
+                    ),
+                    i as i64 * interval,
+                )
+            })
+            .collect();
+        (clean, noisy)
+    }
+
+    /// Attach the timestamps of the given timed points to the given points.
+    fn with_timestamps(points: &[Point3], timed: &[TimedPoint3]) -> Vec<TimedPoint3> {
+        points
+            .iter()
+            .zip(timed)
+            .map(|(p, t)| TimedPoint3::new(*p, t.timestamp))
+            .collect()
+    }
+
+    /// Get the mean distance between the given points and the clean points.
+    fn mean_error(points: &[Point3], clean: &[Point3]) -> f64 {
+        points
+            .iter()
+            .zip(clean)
+            .map(|(a, b)| (a.to_npoint() - b.to_npoint()).norm())
+            .sum::<f64>()
+            / points.len() as f64
+    }
+
+    /// Get the mean absolute angle of the rotational trace of the given points.
+    fn mean_angle(points: &[Point3]) -> f64 {
+        let trace = PointTrace::new(points.to_vec()).to_rot_trace(true);
+        trace
+            .points()
+            .iter()
+            .map(|p| p.radians().abs())
+            .sum::<f64>()
+            / trace.len() as f64
+    }
+
+    #[test]
+    fn parse_profile() {
+        assert_eq!("none".parse(), Ok(FilterProfile::None));
+        assert_eq!(
+            "ema:alpha=0.3".parse(),
+            Ok(FilterProfile::Ema { alpha: 0.3 }),
+        );
+        assert_eq!(
+            "one-euro:beta=0.1".parse(),
+            Ok(FilterProfile::OneEuro {
+                min_cutoff: ONE_EURO_MIN_CUTOFF,
+                beta: 0.1,
+                d_cutoff: ONE_EURO_D_CUTOFF,
+            }),
+        );
+        assert!("kalman:alpha=1".parse::<FilterProfile>().is_err());
+        assert!("ema:alpha=x".parse::<FilterProfile>().is_err());
+        assert!("median".parse::<FilterProfile>().is_err());
+    }
+
+    #[test]
+    fn reduce_noise() {
+        // A line shows noise as angles, which should all be zero
+        let (_, noisy) =
+            noisy_stroke(Stroke::new(Shape::Line { length: 300.0 }).rotation(PI / 5.0));
+        let (circle_clean, circle_noisy) = noisy_stroke(Stroke::new(Shape::Circle {
+            radius: 60.0,
+            clockwise: true,
+        }));
+
+        for profile in &["ema", "one-euro", "kalman"] {
+            let profile: FilterProfile = profile.parse().unwrap();
+            let mut filter = profile.build().unwrap();
+            let mut apply = |points: &[TimedPoint3]| -> Vec<Point3> {
+                filter.reset();
+                points
+                    .iter()
+                    .map(|p| filter.filter(p.point, p.timestamp))
+                    .collect()
+            };
+
+            let raw: Vec<Point3> = noisy.iter().map(|p| p.point).collect();
+            let filtered = apply(&noisy);
+            assert!(
+                mean_angle(&filtered) < mean_angle(&raw) * 0.75,
+                "{} did not reduce angle noise enough",
+                profile,
+            );
+
+            // Position noise on curves should drop too, compared to the filtered clean stroke to
+            // leave out the filter lag
+            let raw: Vec<Point3> = circle_noisy.iter().map(|p| p.point).collect();
+            let filtered = apply(&circle_noisy);
+            let reference = apply(&with_timestamps(&circle_clean, &circle_noisy));
+            assert!(
+                mean_error(&filtered, &reference) < mean_error(&raw, &circle_clean) * 0.75,
+                "{} did not reduce position noise enough",
+                profile,
+            );
+        }
+    }
+}
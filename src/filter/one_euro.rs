@@ -0,0 +1,89 @@
+use std::f64::consts::PI;
+
+use super::{from_array, to_array, Filter};
+use types::Point3;
+
+/// A One-Euro filter.
+///
+/// This is a low-pass filter with a cutoff frequency that adapts to the speed of movement. Slow
+/// movement is smoothed strongly to remove jitter, while fast movement is smoothed less to
+/// reduce lag. See: <http://cristal.univ-lille.fr/~casiez/1euro/>
+#[derive(Clone, Debug)]
+pub struct OneEuroFilter {
+    /// The minimum cutoff frequency in hertz, used when not moving.
+    min_cutoff: f64,
+
+    /// The factor the cutoff frequency increases by with speed, in hertz per millimeter per
+    /// second.
+    beta: f64,
+
+    /// The cutoff frequency in hertz for smoothing the speed.
+    d_cutoff: f64,
+
+    /// The last filtered point, its smoothed velocity and its timestamp.
+    last: Option<([f64; 3], [f64; 3], i64)>,
+}
+
+impl OneEuroFilter {
+    /// Construct a new filter with the given minimum cutoff frequency, speed coefficient and
+    /// speed cutoff frequency.
+    pub fn new(min_cutoff: f64, beta: f64, d_cutoff: f64) -> Self {
+        Self {
+            min_cutoff,
+            beta,
+            d_cutoff,
+            last: None,
+        }
+    }
+}
+
+impl Filter for OneEuroFilter {
+    fn filter(&mut self, point: Point3, timestamp: i64) -> Point3 {
+        let mut coords = to_array(point);
+
+        let (last, last_velocity, last_timestamp) = match self.last {
+            Some(last) => last,
+            None => {
+                self.last = Some((coords, [0.0; 3], timestamp));
+                return point;
+            }
+        };
+
+        // Points without elapsed time can't be filtered, keep the last point
+        let dt = (timestamp - last_timestamp) as f64 / 1_000_000.0;
+        if dt <= 0.0 {
+            return from_array(last);
+        }
+
+        let mut velocity = [0.0; 3];
+        for i in 0..3 {
+            // Smooth the velocity, and adapt the cutoff frequency to it
+            velocity[i] = lerp(
+                last_velocity[i],
+                (coords[i] - last[i]) / dt,
+                smoothing(self.d_cutoff, dt),
+            );
+            let cutoff = self.min_cutoff + self.beta * velocity[i].abs();
+            coords[i] = lerp(last[i], coords[i], smoothing(cutoff, dt));
+        }
+
+        self.last = Some((coords, velocity, timestamp));
+        from_array(coords)
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Get the smoothing factor for a low-pass filter with the given `cutoff` frequency in hertz, for
+/// samples the given `dt` seconds apart.
+fn smoothing(cutoff: f64, dt: f64) -> f64 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+/// Linearly interpolate from `a` to `b` by the given factor.
+fn lerp(a: f64, b: f64, factor: f64) -> f64 {
+    a + factor * (b - a)
+}
@@ -1,66 +1,292 @@
+use std::cmp::min;
 use std::sync::Arc;
 
-use leap::Finger as SensorFinger;
-
+use super::{HandKey, Segment, Segmenter};
+use config::{
+    plane::{FIT_INTERVAL, FIT_WINDOW, REFIT_ANGLE},
+    segmentation::VELOCITY_WINDOW,
+    trace::MAX_AGE,
+};
+use filter::Filter;
 use gesture::GestureController;
-use types::{Model, Point3, PointTrace};
+use types::{
+    HandPart, HandPose, HandSide, Plane, PlaneMode, Point3, PointTrace, RotTrace, RotTraceStream,
+};
 
 /// A fragment.
-// TODO: keep track of the last update time
 // TODO: keep track on what data has been recognized
 #[derive(Debug)]
 pub struct Fragment {
+    /// The key of the hand this fragment is part of.
+    hand: HandKey,
+
+    /// The part of the hand this fragment traces.
+    part: HandPart,
+
+    /// The side of the hand this fragment is part of, if known.
+    side: Option<HandSide>,
+
     /// The raw trace, from the sensor.
     raw: PointTrace,
 
-    /// The processed trace used for recognition as a model.
-    model: Model,
+    /// The noise filter smoothing points from the raw trace before they are sampled, if any.
+    filter: Option<Box<dyn Filter>>,
+
+    /// The processed rotational trace used for recognition, projected onto the XY plane.
+    trace: RotTrace,
+
+    /// The stream incrementally building the rotational trace from the raw trace.
+    stream: RotTraceStream,
+
+    /// The processed rotational trace, projected onto the drawing plane fitted through recent
+    /// points.
+    ///
+    /// This is only maintained while the gesture controller tracks fitted traces, and is empty
+    /// otherwise.
+    fitted: RotTrace,
+
+    /// The stream incrementally building the fitted rotational trace from the raw trace.
+    fitted_stream: RotTraceStream,
+
+    /// Whether the fitted rotational trace is being maintained.
+    fitting: bool,
+
+    /// The number of oldest points of the raw trace that weren't pushed to the fitted stream.
+    fitted_skip: usize,
+
+    /// The number of points pushed since the drawing plane was last fitted.
+    unfitted: usize,
+
+    /// The segmenter splitting the rotational trace into strokes, used in segmentation mode.
+    segmenter: Segmenter,
+
+    /// The last completed stroke, waiting to be matched in segmentation mode.
+    segment: Option<RotTrace>,
+
+    /// The last completed stroke in the fitted rotational trace.
+    fitted_segment: Option<RotTrace>,
+
+    /// The timestamp in microseconds of the sensor frame this fragment was last updated in.
+    last_update: i64,
 
     /// The gesture controller that is used for recongizing gestures.
     gesture_controller: Arc<GestureController>,
 }
 
 impl Fragment {
-    /// Construct a new fragment with empty traces.
-    pub fn new(gesture_controller: Arc<GestureController>) -> Self {
+    /// Construct a new fragment with empty traces, for the given part of the hand with the given
+    /// key.
+    pub fn new(hand: HandKey, part: HandPart, gesture_controller: Arc<GestureController>) -> Self {
         Fragment {
+            hand,
+            part,
+            side: None,
             raw: PointTrace::empty(),
-            model: Model::empty(),
+            filter: gesture_controller.filter().build(),
+            trace: RotTrace::empty(),
+            stream: RotTraceStream::new(),
+            fitted: RotTrace::empty(),
+            fitted_stream: RotTraceStream::new(),
+            fitting: false,
+            fitted_skip: 0,
+            unfitted: 0,
+            segmenter: Segmenter::new(),
+            segment: None,
+            fitted_segment: None,
+            last_update: 0,
             gesture_controller,
         }
     }
 
-    /// Get the processed fragment trace model.
-    pub fn model(&self) -> &Model {
-        &self.model
+    /// Get the key of the hand this fragment is part of.
+    pub fn hand(&self) -> HandKey {
+        self.hand
+    }
+
+    /// Get the part of the hand this fragment traces.
+    pub fn part(&self) -> HandPart {
+        self.part
     }
 
-    /// Push finger data from a sensor frame on the finger trace.
-    /// Then, process the raw data into data we can work with in real-time.
+    /// Get the side of the hand this fragment is part of, if known.
+    pub fn side(&self) -> Option<HandSide> {
+        self.side
+    }
+
+    /// Get the timestamp in microseconds of the sensor frame this fragment was last updated in.
+    pub fn last_update(&self) -> i64 {
+        self.last_update
+    }
+
+    /// Get the processed rotational fragment trace, projected onto the XY plane.
+    pub fn trace(&self) -> &RotTrace {
+        &self.trace
+    }
+
+    /// Get the processed rotational fragment trace, projected onto the drawing plane of the
+    /// given `mode`.
+    pub fn trace_in(&self, mode: PlaneMode) -> &RotTrace {
+        match mode {
+            PlaneMode::Fixed => &self.trace,
+            PlaneMode::Fitted => &self.fitted,
+        }
+    }
+
+    /// Get the last completed stroke in segmentation mode projected onto the drawing plane of the
+    /// given `mode`, if it is waiting to be matched.
+    pub fn segment(&self, mode: PlaneMode) -> Option<&RotTrace> {
+        match mode {
+            PlaneMode::Fixed => self.segment.as_ref(),
+            PlaneMode::Fitted => self.fitted_segment.as_ref(),
+        }
+    }
+
+    /// Get the stroke segments in the rotational trace, for segmentation mode.
+    pub fn segments(&self) -> Vec<Segment> {
+        self.segmenter.segments()
+    }
+
+    /// Push a sensed point of the traced hand part from a sensor frame with the given
+    /// `timestamp` on the trace.
+    /// Then, process the raw data into data we can work with in real-time, and detect gestures
+    /// for the hand on the given `side` in the given `pose`.
     ///
-    /// TODO: remove temporary parameter `process`
-    pub fn process_sensor_finger(&mut self, finger: &SensorFinger, process: bool) {
-        // Add the point to the trace
-        if self.gesture_controller.state().should_track() {
-            self.raw
-                .push(Point3::from(finger.stabilized_tip_position()));
-
-            // TODO: currently resampling/recalculating whole trace,
-            // TODO: reimplement to only sample/calculate the new point
-            // // Calculate the new rotational point based on the new data,
-            // // add it to the processed trace
-            // if let Some(x) = self.raw.to_last_rot_point() {
-            //     self.model.trace_mut().push(x);
-            // }
-            *self.model.trace_mut() = self.raw.to_rot_trace(true);
-
-            // TODO: do some data normalization (scaling, filtering)
+    /// In segmentation mode, gestures are only detected in whole strokes when they end.
+    pub fn process_sensor_point(
+        &mut self,
+        point: Point3,
+        timestamp: i64,
+        side: Option<HandSide>,
+        pose: &HandPose,
+    ) {
+        self.last_update = timestamp;
+        self.side = side;
+
+        // Add the point to the trace, and incrementally update the rotational trace with it
+        let segmentation = self.gesture_controller.segmentation();
+        let state = self.gesture_controller.state();
+        if state.should_track() {
+            let mut truncated = self.raw.push(point, timestamp);
+            if state.should_truncate_age() {
+                truncated += self.raw.truncate_age(MAX_AGE);
+            }
+            let filtered = filter_point(&mut self.filter, point, timestamp);
+            self.stream.push(filtered, &mut self.trace);
+            let removed = self.stream.remove_oldest(truncated, &mut self.trace);
+            self.update_fitted(filtered, truncated);
+
+            // TODO: do some data normalization (scaling)
+
+            // Split the trace into strokes
+            if segmentation {
+                self.segmenter.remove_oldest(removed);
+                let velocity = self.raw.recent_velocity(VELOCITY_WINDOW);
+                if let Some(segment) = self.segmenter.push(velocity, timestamp, self.trace.len()) {
+                    let end = segment.end.unwrap_or_else(|| self.trace.len());
+                    self.segment = Some(self.trace.slice(segment.start, end));
+
+                    // The fitted trace may slightly differ in length after being projected
+                    // again, align the stroke by the end of the traces
+                    let (len, fitted) = (self.trace.len(), self.fitted.len());
+                    let align = |i: usize| min((i + fitted).saturating_sub(len), fitted);
+                    self.fitted_segment = Some(self.fitted.slice(align(segment.start), align(end)));
+                }
+            }
         }
 
         // Pass the processed data to the gesture controller, for recognition
-        if process && self.gesture_controller.state().should_detect() {
+        if state.should_detect() && (!segmentation || self.segment.is_some()) {
             // TODO: do not clone here
-            self.gesture_controller.clone().detect_gesture(self);
+            self.gesture_controller.clone().detect_gesture(self, pose);
+        }
+        self.segment = None;
+        self.fitted_segment = None;
+    }
+
+    /// Update the fitted rotational trace with the newest `filtered` point, after the given
+    /// number of `truncated` oldest points were removed from the raw trace.
+    ///
+    /// The fitted trace is only maintained while the gesture controller tracks fitted traces.
+    /// Once tracked again, it is rebuilt from the recent points of the raw trace.
+    fn update_fitted(&mut self, filtered: Point3, truncated: usize) {
+        if !self.gesture_controller.tracks_fitted() {
+            if self.fitting {
+                self.fitting = false;
+                self.fitted.clear();
+                self.fitted_stream.reset();
+                self.fitted_skip = 0;
+            }
+            return;
+        }
+
+        if !self.fitting {
+            self.fitting = true;
+            self.unfitted = 0;
+            let plane = self
+                .raw
+                .recent_plane(FIT_WINDOW)
+                .unwrap_or_else(|| self.fitted_stream.plane());
+            self.rebuild_fitted(plane);
+            return;
+        }
+
+        // Points that weren't pushed to the fitted stream are truncated first
+        self.fitted_stream.push(filtered, &mut self.fitted);
+        let skipped = min(truncated, self.fitted_skip);
+        self.fitted_skip -= skipped;
+        self.fitted_stream
+            .remove_oldest(truncated - skipped, &mut self.fitted);
+        self.fit_plane();
+    }
+
+    /// Fit the drawing plane through the recent points of the raw trace, every
+    /// `config::plane::FIT_INTERVAL` points.
+    ///
+    /// If the plane differs from the current one by at least `config::plane::REFIT_ANGLE`, the
+    /// fitted rotational trace is rebuilt in it, see `rebuild_fitted`. If no plane can be fitted,
+    /// such as for a straight line, the current plane is kept.
+    fn fit_plane(&mut self) {
+        self.unfitted += 1;
+        if self.unfitted < FIT_INTERVAL {
+            return;
+        }
+        self.unfitted = 0;
+
+        let plane = match self.raw.recent_plane(FIT_WINDOW) {
+            Some(plane) => plane,
+            None => return,
+        };
+        if plane.angle_to(&self.fitted_stream.plane()) < REFIT_ANGLE {
+            return;
+        }
+        self.rebuild_fitted(plane);
+    }
+
+    /// Rebuild the fitted rotational trace, by projecting the points of the raw trace sensed
+    /// within `config::plane::FIT_WINDOW` onto the given `plane`.
+    ///
+    /// Older points were drawn in another plane, and are left out. This also bounds the cost of
+    /// rebuilding, regardless of the length of the raw trace.
+    fn rebuild_fitted(&mut self, plane: Plane) {
+        let recent = self.raw.recent(FIT_WINDOW);
+        self.fitted_skip = self.raw.points().len() - recent.len();
+
+        // Filter the points with a new filter, to keep the state of the current one
+        let mut filter = self.gesture_controller.filter().build();
+        self.fitted.clear();
+        self.fitted_stream = RotTraceStream::with_plane(plane);
+        for p in recent {
+            let filtered = filter_point(&mut filter, p.point, p.timestamp);
+            self.fitted_stream.push(filtered, &mut self.fitted);
+        }
+    }
+
+    /// Clear the trace after a gesture was detected in it, to prevent overlapping detections.
+    ///
+    /// In segmentation mode strokes never overlap, so the trace is kept as is.
+    pub fn clear_detected(&mut self) {
+        if !self.gesture_controller.segmentation() {
+            self.clear_most();
         }
     }
 
@@ -71,6 +297,92 @@ impl Fragment {
     /// `KEEP_POINTS`, no points are removed from the trace.
     pub fn clear_most(&mut self) {
         self.raw.clear_most();
-        self.model.clear();
+
+        // Rebuild the rotational trace from the points that are left
+        self.trace.clear();
+        self.stream.reset();
+        self.fitted.clear();
+        self.fitted_stream.reset();
+        if let Some(filter) = &mut self.filter {
+            filter.reset();
+        }
+        self.fitted_skip = 0;
+        for p in self.raw.points() {
+            let filtered = filter_point(&mut self.filter, p.point, p.timestamp);
+            self.stream.push(filtered, &mut self.trace);
+            if self.fitting {
+                self.fitted_stream.push(filtered, &mut self.fitted);
+            }
+        }
+        self.segmenter.reset();
+    }
+
+    /// Reset the fragment, clearing all trace data.
+    ///
+    /// This ensures a new stroke doesn't join up with an old one.
+    pub fn reset(&mut self) {
+        self.raw.clear();
+        self.trace.clear();
+        self.stream.reset();
+        self.fitted.clear();
+        self.fitted_stream.reset();
+        self.fitted_skip = 0;
+        self.unfitted = 0;
+        self.segmenter.reset();
+        self.segment = None;
+        self.fitted_segment = None;
+        if let Some(filter) = &mut self.filter {
+            filter.reset();
+        }
+    }
+}
+
+/// Smooth the given raw `point`, sensed at the given `timestamp`, with the given noise `filter`.
+///
+/// If no filter is used, the point is returned as is.
+fn filter_point(filter: &mut Option<Box<dyn Filter>>, point: Point3, timestamp: i64) -> Point3 {
+    match filter {
+        Some(filter) => filter.filter(point, timestamp),
+        None => point,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::sample::DISTANCE;
+    use filter::FilterProfile;
+    use gesture::GestureState;
+    use store::TemplateStore;
+    use types::{FingerType, Zone};
+
+    #[test]
+    fn keep_old_points_while_recording() {
+        let controller = Arc::new(GestureController::new(
+            Arc::new(TemplateStore::new()),
+            false,
+            FilterProfile::None,
+            Zone::Any,
+        ));
+        let pose = HandPose::default();
+
+        // Draw a straight line for twice the maximum age, in the given state
+        let draw = |state| {
+            controller.set_state(state);
+            let mut fragment = Fragment::new(
+                (0, 0),
+                HandPart::Finger(FingerType::Index),
+                controller.clone(),
+            );
+            for i in 0..=200 {
+                let point = Point3::new(i as f64 * DISTANCE, 0.0, 0.0);
+                fragment.process_sensor_point(point, i * MAX_AGE / 100, None, &pose);
+            }
+            fragment.trace().len()
+        };
+
+        // Old points are truncated, unless recording
+        assert!(draw(GestureState::Normal) <= 101);
+        assert!(draw(GestureState::Recording) >= 190);
     }
 }
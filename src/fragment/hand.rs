@@ -1,67 +1,195 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use leap::{FingerType, Hand as SensorHand};
-
-use super::Fragment;
-use gesture::GestureController;
-use types::Model;
+use super::{Fragment, HandKey, Segment};
+use config::fragment::FRAGMENT_EXPIRY;
+use gesture::{GestureController, HeldPosture};
+use sensor::HandFrame;
+use types::{FingerType, HandPart, HandPose, HandSide, PlaneMode, Point3, RotTrace};
 
 /// A hand with traces.
 #[derive(Debug)]
 pub struct Hand {
-    /// The fingers on this hand, grouped by their finger types.
-    fingers: HashMap<FingerType, Fragment>,
+    /// The key identifying this hand.
+    key: HandKey,
+
+    /// The side of this hand, if known.
+    side: Option<HandSide>,
+
+    /// The traced parts of this hand, such as the fingers and palm.
+    parts: HashMap<HandPart, Fragment>,
+
+    /// The current pose of this hand.
+    pose: HandPose,
+
+    /// The posture this hand is holding, if any.
+    posture: Option<HeldPosture>,
+
+    /// The sensed positions of the hand parts in the latest sensor frame.
+    positions: Vec<(HandPart, Point3)>,
+
+    /// The timestamp in microseconds of the sensor frame this hand was last seen in.
+    last_update: i64,
 
     /// Get gesture controller that is used for gesture detection.
     gesture_controller: Arc<GestureController>,
 }
 
 impl Hand {
-    /// Construct a new hand.
-    pub fn new(gesture_controller: Arc<GestureController>) -> Self {
+    /// Construct a new hand, identified by the given `key`.
+    pub fn new(key: HandKey, gesture_controller: Arc<GestureController>) -> Self {
         Hand {
-            fingers: HashMap::new(),
+            key,
+            side: None,
+            parts: HashMap::new(),
+            pose: HandPose::default(),
+            posture: None,
+            positions: Vec::new(),
+            last_update: 0,
             gesture_controller,
         }
     }
 
-    /// Find the longest model in this hand.
+    /// Get the timestamp in microseconds of the sensor frame this hand was last seen in.
+    pub fn last_update(&self) -> i64 {
+        self.last_update
+    }
+
+    /// Get the current pose of this hand.
+    pub fn pose(&self) -> &HandPose {
+        &self.pose
+    }
+
+    /// Find the longest trace in this hand, projected onto the drawing plane of the given
+    /// `mode`.
     ///
-    /// If there is no model, `None` is returned instead.
-    pub fn longest_model(&self) -> Option<Model> {
-        self.fingers
+    /// Traces of equal length are picked in the order of their hand parts, preferring fingers
+    /// from the thumb to the pinky over the palm.
+    /// If there is no trace, `None` is returned instead.
+    pub fn longest_trace(&self, mode: PlaneMode) -> Option<HandTrace> {
+        self.parts
             .values()
-            .map(|f| f.model())
-            .max_by_key(|m| m.len())
-            .cloned()
+            .max_by(|a, b| {
+                a.trace_in(mode)
+                    .len()
+                    .cmp(&b.trace_in(mode).len())
+                    .then_with(|| b.part().cmp(&a.part()))
+            })
+            .map(|f| HandTrace {
+                side: self.side,
+                part: f.part(),
+                trace: f.trace_in(mode).clone(),
+                end: f.last_update(),
+            })
     }
 
-    /// Process a sensor hand frame from the sensor.
-    pub fn process_sensor_hand(&mut self, hand: &SensorHand) {
-        // TODO: do not only walk through the extended fingers here
-        for f in hand.fingers().extended().iter() {
-            // Clone the gesture controller for new fragments
-            // TODO: only clone for new fragments to improve performance
-            let gesture_controller = self.gesture_controller.clone();
-
-            // Only process extended index fingers
-            // TODO: process all fingers after debugging
-            let process = f.type_enum() == FingerType::Index && f.is_extended();
-
-            // Process the sensor finger on the fragment, create it if it doesn't exist
-            self.fingers
-                .entry(f.type_enum())
-                .or_insert_with(|| Fragment::new(gesture_controller))
-                .process_sensor_finger(&f, process);
+    /// Process a sensor hand frame from a sensor frame with the given `timestamp`.
+    ///
+    /// Fingers that aren't extended aren't traced, and their trace is reset. Parts outside the
+    /// interaction zone aren't traced either, and their trace is reset when they leave it.
+    pub fn process_sensor_hand(&mut self, hand: &HandFrame, timestamp: i64) {
+        self.last_update = timestamp;
+        self.pose = HandPose::from_frame(hand);
+        if hand.side.is_some() {
+            self.side = hand.side;
         }
+        self.positions = hand
+            .fingers
+            .iter()
+            .map(|f| (HandPart::Finger(f.finger_type), f.tip))
+            .chain(hand.palm.map(|palm| (HandPart::Palm, palm)))
+            .collect();
+
+        // Detect postures held by this hand, while it is in the interaction zone
+        let zone = self.gesture_controller.zone();
+        let in_zone = match hand.palm {
+            Some(palm) => zone.contains(palm),
+            None => hand.fingers.iter().any(|f| zone.contains(f.tip)),
+        };
+        if !in_zone {
+            self.posture = None;
+        } else if self.gesture_controller.state().should_detect() {
+            self.gesture_controller.detect_posture(
+                &mut self.posture,
+                self.side,
+                &self.pose,
+                timestamp,
+            );
+        }
+
+        for f in &hand.fingers {
+            let part = HandPart::Finger(f.finger_type);
+            if f.extended && zone.contains(f.tip) {
+                self.process_sensor_point(part, f.tip, timestamp);
+            } else if let Some(fragment) = self.parts.get_mut(&part) {
+                fragment.reset();
+            }
+        }
+        match hand.palm {
+            Some(palm) if zone.contains(palm) => {
+                self.process_sensor_point(HandPart::Palm, palm, timestamp)
+            }
+            Some(_) => {
+                if let Some(fragment) = self.parts.get_mut(&HandPart::Palm) {
+                    fragment.reset();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Process a sensed point of the given hand `part` on its fragment, create it if it doesn't
+    /// exist.
+    fn process_sensor_point(&mut self, part: HandPart, point: Point3, timestamp: i64) {
+        // Clone the gesture controller for new fragments
+        // TODO: only clone for new fragments to improve performance
+        let gesture_controller = self.gesture_controller.clone();
+        let key = self.key;
+
+        self.parts
+            .entry(part)
+            .or_insert_with(|| Fragment::new(key, part, gesture_controller))
+            .process_sensor_point(point, timestamp, self.side, &self.pose);
+    }
+
+    /// Drop fragments that haven't been updated within `config::fragment::FRAGMENT_EXPIRY`,
+    /// relative to the given `timestamp` of the current sensor frame.
+    pub fn collect_garbage(&mut self, timestamp: i64) {
+        self.parts
+            .retain(|_, fragment| timestamp - fragment.last_update() <= FRAGMENT_EXPIRY);
+    }
+
+    /// Get the sensed positions of the hand parts in the latest sensor frame, for visualisation.
+    pub fn live_positions(&self) -> &[(HandPart, Point3)] {
+        &self.positions
     }
 
     // TODO: this is temporary
-    pub fn get_live_model(&self) -> Option<Model> {
+    pub fn get_live_traces(&self) -> Vec<(HandPart, RotTrace, Vec<Segment>)> {
         // TODO: do not clone here
-        self.fingers
-            .get(&FingerType::Index)
-            .map(|fragment| fragment.model().clone())
+        [HandPart::Finger(FingerType::Index), HandPart::Palm]
+            .iter()
+            .filter_map(|part| {
+                self.parts
+                    .get(part)
+                    .map(|fragment| (*part, fragment.trace().clone(), fragment.segments()))
+            })
+            .collect()
     }
 }
+
+/// A trace of a hand, taken from one of its fragments.
+#[derive(Clone, Debug)]
+pub struct HandTrace {
+    /// The side of the hand, if known.
+    pub side: Option<HandSide>,
+
+    /// The hand part that was traced.
+    pub part: HandPart,
+
+    /// The rotational trace.
+    pub trace: RotTrace,
+
+    /// The timestamp in microseconds the trace was last updated at.
+    pub end: i64,
+}
@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use leap::HandList as SensorHandList;
-
-use super::Hand;
+use super::{Hand, HandTrace, Segment};
+use config::fragment::HAND_EXPIRY;
 use gesture::GestureController;
-use types::Model;
+use sensor::{HandFrame, SensorId};
+use types::{HandPart, HandPose, PlaneMode, Point3, RotTrace};
+
+/// A key identifying a hand, being the ID of the sensor tracking it and the hand ID obtained from
+/// that sensor.
+///
+/// Hand IDs are only unique for a single sensor, this keeps hands of multiple sensors apart.
+pub type HandKey = (SensorId, i32);
 
 /// A hand manager.
 #[derive(Debug)]
 pub struct HandManager {
-    /// A hashmap with hands, grouped by their hand ID obtained from the sensor.
+    /// A hashmap with hands, grouped by their hand key.
     ///
     /// The hashmap and the hands itself are wrapped in a mutex to allow using and mutating these
     /// constructs from any contect by the use of locking.
-    hands: Mutex<HashMap<i32, Arc<Mutex<Hand>>>>,
+    hands: Mutex<HashMap<HandKey, Arc<Mutex<Hand>>>>,
 }
 
 impl HandManager {
@@ -25,62 +31,85 @@ impl HandManager {
         }
     }
 
-    /// Add the given hand with the given ID to the internal list of hands.
+    /// Add the given hand with the given key to the internal list of hands.
     ///
-    /// Note: if a hand with this ID already exists, it is replaced.
-    pub fn add(&self, id: i32, hand: Arc<Mutex<Hand>>) {
+    /// Note: if a hand with this key already exists, it is replaced.
+    pub fn add(&self, key: HandKey, hand: Arc<Mutex<Hand>>) {
         self.hands
             .lock()
             .expect("failed to lock hands in frament manager, for adding a new hand")
-            .insert(id, hand);
+            .insert(key, hand);
     }
 
     /// Get a hand from the list.
-    /// If no hand exists with this ID, `None` is returned instead.
-    pub fn get(&self, id: i32) -> Option<Arc<Mutex<Hand>>> {
+    /// If no hand exists with this key, `None` is returned instead.
+    pub fn get(&self, key: HandKey) -> Option<Arc<Mutex<Hand>>> {
         self.hands
             .lock()
             .expect("failed to lock hands in fragment manager, for obtaining a hand")
-            .get(&id)
+            .get(&key)
             .cloned()
     }
 
-    /// Find the longest model from the hand list.
-    /// If no model exists, `None` is returned instead.
-    pub fn longest_model(&self) -> Option<Model> {
-        self.hands
+    /// Find the longest trace of each hand, projected onto the drawing plane of the given `mode`.
+    ///
+    /// The traces are ordered from longest to shortest. Hands without a trace are skipped.
+    pub fn longest_traces(&self, mode: PlaneMode) -> Vec<HandTrace> {
+        let mut traces: Vec<_> = self
+            .hands
             .lock()
-            .expect("failed to lock hands in fragment manager, for obtaining longest model")
+            .expect("failed to lock hands in fragment manager, for obtaining longest traces")
             .values()
             .filter_map(|h| {
                 h.lock()
-                    .expect("failed to lock hand to find longest model")
-                    .longest_model()
+                    .expect("failed to lock hand to find longest trace")
+                    .longest_trace(mode)
             })
-            .max_by_key(|m| m.len())
+            .filter(|t| !t.trace.points().is_empty())
+            .collect();
+        traces.sort_by(|a, b| b.trace.len().cmp(&a.trace.len()));
+        traces
+    }
+
+    /// Get the current pose of the most recently seen hand.
+    ///
+    /// Timestamps of different sensors aren't comparable, so with multiple sensors any of their
+    /// most recently seen hands may be picked.
+    /// If there is no hand, `None` is returned instead.
+    pub fn latest_pose(&self) -> Option<HandPose> {
+        self.hands
+            .lock()
+            .expect("failed to lock hands in hand manager, for obtaining latest pose")
+            .values()
+            .map(|h| h.lock().expect("failed to lock hand to obtain pose"))
+            .max_by_key(|h| h.last_update())
+            .map(|h| h.pose().clone())
     }
 
-    /// Add a hand with the given hand ID.
+    /// Add a hand with the given hand key.
     ///
-    /// Note: if a hand with the given ID already exists, it is returned instead.
+    /// Note: if a hand with the given key already exists, it is returned instead.
     pub fn create_hand(
         &self,
-        id: i32,
+        key: HandKey,
         gesture_controller: &Arc<GestureController>,
     ) -> Arc<Mutex<Hand>> {
         self.hands
             .lock()
             .expect("failed to lock hands manager to add a new hand")
-            .entry(id)
-            .or_insert_with(|| Arc::new(Mutex::new(Hand::new(gesture_controller.clone()))))
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(Hand::new(key, gesture_controller.clone()))))
             .clone()
     }
 
-    /// Process a hand list frame from the sensor.
+    /// Process a hand list from a frame with the given `timestamp`, from the sensor with the
+    /// given ID.
     #[inline]
     pub fn process_sensor_hand_list(
         &self,
-        hand_list: SensorHandList,
+        sensor: SensorId,
+        timestamp: i64,
+        hand_list: &[HandFrame],
         guesture_controller: &Arc<GestureController>,
     ) {
         // Get the gesture controller state
@@ -94,41 +123,81 @@ impl HandManager {
         // Loop through all hands
         for sensor_hand in hand_list.iter() {
             // Obtain our hand or create a new one
-            let hand = self.get(sensor_hand.id()).unwrap_or_else(|| {
+            let key = (sensor, sensor_hand.id);
+            let hand = self.get(key).unwrap_or_else(|| {
                 // Create hand in global fragment manager, add it to this manager
-                let hand = self.create_hand(sensor_hand.id(), guesture_controller);
-                self.add(sensor_hand.id(), hand.clone());
+                let hand = self.create_hand(key, guesture_controller);
+                self.add(key, hand.clone());
                 hand
             });
 
             // Process the sensor hand
             hand.lock()
                 .expect("failed to unlock hand for updating traces")
-                .process_sensor_hand(&sensor_hand);
+                .process_sensor_hand(sensor_hand, timestamp);
         }
 
-        // Retain hands from the hands map that aren't in view anymore
+        // Drop hands and fragments that haven't been in view for a while
         if state.should_cleanup() {
-            self.retain_hands(&hand_list);
+            self.collect_garbage(sensor, timestamp);
         }
     }
 
-    /// Only retain hands in this hand manager that are part of the given `hand_list`.
-    /// Other hands are drained from the list.
-    pub fn retain_hands(&self, hand_list: &SensorHandList) {
+    /// Garbage collect hands of the sensor with the given ID that haven't been seen within
+    /// `config::fragment::HAND_EXPIRY`, relative to the given `timestamp` of the current sensor
+    /// frame. Stale fragments are dropped from the remaining hands of this sensor.
+    ///
+    /// Timestamps of different sensors aren't comparable, so hands of other sensors are kept.
+    pub fn collect_garbage(&self, sensor: SensorId, timestamp: i64) {
+        self.hands
+            .lock()
+            .expect("failed to lock hands in hand manager, for garbage collecting old hands")
+            .retain(|&(hand_sensor, _), hand| {
+                if hand_sensor != sensor {
+                    return true;
+                }
+
+                let mut hand = hand
+                    .lock()
+                    .expect("failed to lock hand for garbage collection");
+                if timestamp - hand.last_update() > HAND_EXPIRY {
+                    return false;
+                }
+                hand.collect_garbage(timestamp);
+                true
+            });
+    }
+
+    /// Remove all hands tracked by the sensor with the given ID.
+    pub fn remove_sensor_hands(&self, sensor: SensorId) {
         self.hands
             .lock()
-            .expect("failed to lock hands in hand manager, for decaying old hands")
-            .retain(|&hand_id, _| hand_list.iter().any(|h| h.id() == hand_id));
+            .expect("failed to lock hands in hand manager, for removing sensor hands")
+            .retain(|&(hand_sensor, _), _| hand_sensor != sensor);
     }
 
     // TODO: this is temporary
-    pub fn get_live_models(&self) -> Vec<Model> {
+    pub fn get_live_traces(&self) -> Vec<(HandPart, RotTrace, Vec<Segment>)> {
+        self.hands
+            .lock()
+            .expect("failed to lock hands manager list")
+            .values()
+            .flat_map(|hand| hand.lock().expect("failed to lock hand").get_live_traces())
+            .collect()
+    }
+
+    /// Get the latest sensed positions of hand parts, for visualisation.
+    pub fn get_live_positions(&self) -> Vec<(HandPart, Point3)> {
         self.hands
             .lock()
             .expect("failed to lock hands manager list")
             .values()
-            .filter_map(|hand| hand.lock().expect("failed to lock hand").get_live_model())
+            .flat_map(|hand| {
+                hand.lock()
+                    .expect("failed to lock hand")
+                    .live_positions()
+                    .to_vec()
+            })
             .collect()
     }
 
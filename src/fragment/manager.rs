@@ -1,10 +1,9 @@
 use std::sync::Arc;
 
-use leap::HandList as SensorHandList;
-
-use super::HandManager;
+use super::{HandManager, HandTrace, Segment};
 use gesture::GestureController;
-use types::Model;
+use sensor::{HandFrame, SensorId};
+use types::{HandPart, HandPose, PlaneMode, Point3, RotTrace};
 
 /// A fragment manager.
 #[derive(Debug)]
@@ -25,16 +24,29 @@ impl FragmentManager {
         }
     }
 
-    /// Get the longest model from the fragment manager.
+    /// Get the longest trace of each hand from the fragment manager, projected onto the drawing
+    /// plane of the given `mode`.
+    ///
+    /// The traces are ordered from longest to shortest.
+    pub fn longest_traces(&self, mode: PlaneMode) -> Vec<HandTrace> {
+        self.hand.longest_traces(mode)
+    }
+
+    /// Get the current pose of the most recently seen hand.
     ///
-    /// If no model is available, `None` is returned instead.
-    pub fn longest_model(&self) -> Option<Model> {
-        self.hand.longest_model()
+    /// If there is no hand, `None` is returned instead.
+    pub fn latest_pose(&self) -> Option<HandPose> {
+        self.hand.latest_pose()
     }
 
     // TODO: this is temporary
-    pub fn live_models(&self) -> Vec<Model> {
-        self.hand.get_live_models()
+    pub fn live_traces(&self) -> Vec<(HandPart, RotTrace, Vec<Segment>)> {
+        self.hand.get_live_traces()
+    }
+
+    /// Get the latest sensed positions of hand parts, for visualisation.
+    pub fn live_positions(&self) -> Vec<(HandPart, Point3)> {
+        self.hand.get_live_positions()
     }
 
     /// Clear the hands.
@@ -42,12 +54,23 @@ impl FragmentManager {
         self.hand.clear();
     }
 
-    /// Process a hand list frame from the sensor.
+    /// Process a hand list from a frame with the given `timestamp`, from the sensor with the
+    /// given ID.
     #[inline]
-    pub fn process_sensor_hand_list(&self, hand_list: SensorHandList) {
+    pub fn process_sensor_hand_list(
+        &self,
+        sensor: SensorId,
+        timestamp: i64,
+        hand_list: &[HandFrame],
+    ) {
         self.hand
-            .process_sensor_hand_list(hand_list, &self.gesture_controller);
+            .process_sensor_hand_list(sensor, timestamp, hand_list, &self.gesture_controller);
     }
 
-    // TODO: create a method for garbage collecting hands that haven't been updated in a while
+    /// Remove all hands tracked by the sensor with the given ID.
+    ///
+    /// This should be used when a sensor is removed, to drop its stale hands.
+    pub fn remove_sensor_hands(&self, sensor: SensorId) {
+        self.hand.remove_sensor_hands(sensor);
+    }
 }
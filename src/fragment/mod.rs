@@ -2,9 +2,11 @@ pub mod fragment;
 pub mod hand;
 pub mod hand_manager;
 pub mod manager;
+pub mod segmenter;
 
 // Re-export
 pub use self::fragment::Fragment;
-pub use self::hand::Hand;
-pub use self::hand_manager::HandManager;
+pub use self::hand::{Hand, HandTrace};
+pub use self::hand_manager::{HandKey, HandManager};
 pub use self::manager::FragmentManager;
+pub use self::segmenter::{Segment, Segmenter};
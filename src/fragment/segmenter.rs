@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+
+use config::segmentation::{DWELL_TIME, END_VELOCITY, MIN_POINTS, START_VELOCITY};
+
+/// A stroke segment in a rotational trace, with bounds `[start, end]` as point indices.
+#[derive(Copy, Clone, Debug, Serialize, PartialEq)]
+pub struct Segment {
+    /// The index of the first point in the segment.
+    pub start: usize,
+
+    /// The index after the last point in the segment, or `None` if the stroke is still ongoing.
+    pub end: Option<usize>,
+}
+
+/// A stroke segmenter, splitting a rotational trace into strokes by finger velocity.
+///
+/// A stroke starts when the finger speeds up to `config::segmentation::START_VELOCITY`, and ends
+/// once it has been slower than `config::segmentation::END_VELOCITY` for
+/// `config::segmentation::DWELL_TIME`. A stroke also ends if the finger wasn't sensed for that
+/// time, such as when the hand left the view of the sensor.
+#[derive(Debug, Default)]
+pub struct Segmenter {
+    /// The ongoing stroke, if any.
+    stroke: Option<Stroke>,
+
+    /// The completed segments still in the trace, oldest first.
+    segments: VecDeque<Segment>,
+
+    /// The timestamp in microseconds and trace length of the last processed velocity.
+    last: Option<(i64, usize)>,
+}
+
+/// An ongoing stroke.
+#[derive(Copy, Clone, Debug)]
+struct Stroke {
+    /// The index of the first point in the stroke.
+    start: usize,
+
+    /// The timestamp in microseconds and trace length at which the stroke was paused, if paused.
+    paused: Option<(i64, usize)>,
+}
+
+impl Segmenter {
+    /// Construct a new segmenter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process the finger `velocity` in millimeters per second, measured at the given `timestamp`
+    /// when the trace had the given length `len`.
+    ///
+    /// If a stroke has ended, its segment is returned. Strokes shorter than
+    /// `config::segmentation::MIN_POINTS` are dropped.
+    pub fn push(&mut self, velocity: Option<f64>, timestamp: i64, len: usize) -> Option<Segment> {
+        let velocity = velocity.unwrap_or(0.0);
+        let last = self.last.replace((timestamp, len));
+
+        // Start a new stroke when speeding up
+        let mut stroke = match self.stroke {
+            Some(stroke) => stroke,
+            None => {
+                if velocity >= START_VELOCITY {
+                    self.stroke = Some(Stroke {
+                        start: len,
+                        paused: None,
+                    });
+                }
+                return None;
+            }
+        };
+
+        // Track pauses, and end the stroke once paused for long enough
+        if let Some(last) = last.filter(|(since, _)| timestamp - since >= DWELL_TIME) {
+            stroke.paused = stroke.paused.or(Some(last));
+        } else if velocity >= END_VELOCITY {
+            stroke.paused = None;
+        } else if stroke.paused.is_none() {
+            stroke.paused = Some((timestamp, len));
+        }
+        self.stroke = Some(stroke);
+        let end = match stroke.paused {
+            Some((since, end)) if timestamp - since >= DWELL_TIME => end,
+            _ => return None,
+        };
+        self.stroke = None;
+
+        if end - stroke.start < MIN_POINTS {
+            return None;
+        }
+        let segment = Segment {
+            start: stroke.start,
+            end: Some(end),
+        };
+        self.segments.push_back(segment);
+        Some(segment)
+    }
+
+    /// Remove the given number of oldest points, after they were removed from the trace.
+    ///
+    /// Segments are shifted to keep them in sync with the trace, segments that are gone are
+    /// dropped.
+    pub fn remove_oldest(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        let shift = |i: usize| i.saturating_sub(count);
+        for segment in &mut self.segments {
+            segment.start = shift(segment.start);
+            segment.end = segment.end.map(shift);
+        }
+        self.segments.retain(|segment| segment.end != Some(0));
+
+        if let Some(stroke) = &mut self.stroke {
+            stroke.start = shift(stroke.start);
+            stroke.paused = stroke.paused.map(|(since, end)| (since, shift(end)));
+        }
+        self.last = self.last.map(|(since, len)| (since, shift(len)));
+    }
+
+    /// Get the completed segments still in the trace and the ongoing stroke, oldest first.
+    pub fn segments(&self) -> Vec<Segment> {
+        self.segments
+            .iter()
+            .cloned()
+            .chain(self.stroke.map(|stroke| Segment {
+                start: stroke.start,
+                end: None,
+            }))
+            .collect()
+    }
+
+    /// Reset the segmenter, dropping all segments.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_by_velocity() {
+        let mut segmenter = Segmenter::new();
+
+        // Idle, then speed up and draw a stroke
+        assert_eq!(segmenter.push(Some(10.0), 0, 0), None);
+        assert_eq!(segmenter.push(Some(200.0), 10_000, 2), None);
+        assert_eq!(segmenter.push(Some(100.0), 20_000, 8), None);
+
+        // Pause, the stroke ends after the dwell time at the point it was paused
+        assert_eq!(segmenter.push(Some(20.0), 30_000, 12), None);
+        assert_eq!(segmenter.push(None, 100_000, 12), None);
+        assert_eq!(
+            segmenter.push(None, 30_000 + DWELL_TIME, 13),
+            Some(Segment {
+                start: 2,
+                end: Some(12),
+            }),
+        );
+
+        // Segments shift along with the trace
+        segmenter.remove_oldest(5);
+        assert_eq!(
+            segmenter.segments(),
+            vec![Segment {
+                start: 0,
+                end: Some(7),
+            }],
+        );
+
+        // Strokes end when the finger isn't sensed for a while
+        assert_eq!(segmenter.push(Some(200.0), 300_000, 7), None);
+        assert_eq!(segmenter.push(Some(200.0), 310_000, 14), None);
+        assert_eq!(
+            segmenter.push(None, 310_000 + DWELL_TIME, 15),
+            Some(Segment {
+                start: 7,
+                end: Some(14),
+            }),
+        );
+
+        // Short strokes are dropped
+        assert_eq!(segmenter.push(Some(200.0), 500_000, 16), None);
+        assert_eq!(segmenter.push(Some(0.0), 510_000, 17), None);
+        assert_eq!(segmenter.push(Some(0.0), 510_000 + DWELL_TIME, 17), None);
+        assert_eq!(segmenter.segments().len(), 2);
+    }
+}
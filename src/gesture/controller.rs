@@ -1,13 +1,22 @@
 use std::{
+    collections::HashSet,
     fmt::{self, Display},
-    io::Result,
+    io::{Error, ErrorKind, Result},
     mem,
     sync::{Arc, Mutex},
 };
 
-use fragment::{Fragment, FragmentManager};
+use super::Detection;
+use config::{
+    recognition::MULTI_TRACE_WINDOW, recording::ACTIVE_TRACE_RATIO, scale::POINTS as SCALE_POINTS,
+};
+use filter::FilterProfile;
+use fragment::{Fragment, FragmentManager, HandKey, Segment};
 use store::TemplateStore;
-use types::{Model, Template};
+use types::{
+    HandPart, HandPose, HandSide, MatchScore, Model, ModelTrace, Point3, PostureTemplate, RotTrace,
+    Template, TemplateOptions, Zone,
+};
 
 /// Gesture controller, for controlling/orchestrating recognition and recording
 #[derive(Debug)]
@@ -18,8 +27,26 @@ pub struct GestureController {
     /// The state.
     state: Mutex<State>,
 
-    /// A list of detected templates.
-    detected: Mutex<Vec<Template>>,
+    /// A list of detected gestures.
+    detected: Mutex<Vec<Detection>>,
+
+    /// Whether to segment traces into strokes, and only match whole strokes.
+    segmentation: bool,
+
+    /// The profile of the noise filter to use on sensed points.
+    filter: FilterProfile,
+
+    /// The interaction zone points are tracked in.
+    zone: Zone,
+
+    /// Traces of multi-trace templates that recently matched, waiting for the other traces.
+    partial: Mutex<Vec<PartialMatch>>,
+
+    /// Fragments to clear because they were part of a detected multi-trace template.
+    ///
+    /// Fragments are cleared when they are processed next, as their hand may not be locked
+    /// while detecting.
+    pending_clears: Mutex<HashSet<(HandKey, HandPart)>>,
 
     /// The fragment manager.
     pub fragment_manager: Mutex<Option<Arc<FragmentManager>>>,
@@ -27,50 +54,277 @@ pub struct GestureController {
 
 impl GestureController {
     /// Construct a new gesture controller.
-    pub fn new(store: Arc<TemplateStore>) -> Self {
+    ///
+    /// If `segmentation` is set, traces are segmented into strokes by pause and velocity, and
+    /// gestures are only detected in whole strokes. Sensed points are smoothed with the noise
+    /// filter described by the given `filter` profile. Only points within the given `zone` are
+    /// tracked.
+    pub fn new(
+        store: Arc<TemplateStore>,
+        segmentation: bool,
+        filter: FilterProfile,
+        zone: Zone,
+    ) -> Self {
         Self {
             store,
             state: Mutex::new(State::default()),
             detected: Mutex::new(Vec::new()),
+            segmentation,
+            filter,
+            zone,
+            partial: Mutex::new(Vec::new()),
+            pending_clears: Mutex::new(HashSet::new()),
             fragment_manager: Mutex::new(None),
         }
     }
 
-    /// Create a new template based on the current fragment manager data, with the given name and
-    /// trim positions.
-    pub fn create(&self, name: String, from: usize, to: usize) -> Result<()> {
-        // Grab the longest model we can find
-        // TODO: improve this later to support multiple fragments in a template
-        let mut model = self
+    /// Create a new template based on the current fragment manager data, with the given name,
+    /// trim positions and matching options.
+    ///
+    /// The longest trace of every hand that was active while recording is captured in the model,
+    /// as described by `config::recording::ACTIVE_TRACE_RATIO`. The trim positions apply to the
+    /// longest trace, other traces are trimmed proportionally.
+    ///
+    /// The template applies to the hand parts the recorded traces were traced with. Traces of scale
+    /// invariant templates are resampled to `config::scale::POINTS` points.
+    pub fn create(
+        &self,
+        name: String,
+        from: usize,
+        to: usize,
+        options: TemplateOptions,
+    ) -> Result<()> {
+        // Grab the longest trace of each hand, longest first
+        let mut traces = self
             .fragment_manager
             .lock()
             .expect("failed to lock fragment manager to create new template")
             .as_ref()
             .expect("failed to unwrap fragment manager to create new template")
-            .longest_model()
-            // TODO: do not unwrap, return a proper error
-            .expect("no model with trace available");
+            .longest_traces(options.plane);
+        let longest = match traces.first() {
+            Some(t) => t.trace.len(),
+            None => return Err(Error::new(ErrorKind::NotFound, "no trace available")),
+        };
+
+        // Only keep hands that were active, and trim their traces
+        traces.retain(|t| t.trace.len() as f64 >= longest as f64 * ACTIVE_TRACE_RATIO);
+        for t in &mut traces {
+            let len = t.trace.len();
+            t.trace.trim(from * len / longest, to * len / longest);
+            if options.scale_invariant {
+                t.trace = t.trace.resample(SCALE_POINTS);
+            }
+        }
 
-        // Trim the model
-        model.trim(from, to);
+        // Build the model, with trace offsets relative to the last ending trace
+        let offsets = trace_offsets(&traces.iter().map(|t| t.end).collect::<Vec<_>>());
+        let parts: Vec<HandPart> = traces.iter().map(|t| t.part).collect();
+        let model = Model::from_traces(
+            traces
+                .into_iter()
+                .zip(offsets)
+                .enumerate()
+                .map(|(hand, (t, offset))| {
+                    let name = match t.side {
+                        Some(side) => format!("{} {}", side, t.part),
+                        None => format!("hand {}: {}", hand + 1, t.part),
+                    };
+                    ModelTrace::new(name, hand, t.side, offset, t.trace)
+                })
+                .collect(),
+        );
 
         // Create the template
-        self.store.add(Template::new(name, model))
+        self.store
+            .add(Template::for_parts(name, &parts, model).with_options(options))
+    }
+
+    /// Create a new posture template based on the current pose of the most recently seen hand,
+    /// with the given name.
+    pub fn create_posture(&self, name: String) -> Result<()> {
+        let pose = self
+            .fragment_manager
+            .lock()
+            .expect("failed to lock fragment manager to create new posture")
+            .as_ref()
+            .expect("failed to unwrap fragment manager to create new posture")
+            .latest_pose()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no hand pose available"))?;
+
+        self.store
+            .add_posture(PostureTemplate::from_pose(name, &pose))
     }
 
-    /// Attempt to detect gestures in the given collected fragment.
+    /// Attempt to detect gestures in the given collected fragment, of a hand in the given `pose`.
+    ///
+    /// Templates constraining the hand pose are only detected if the pose satisfies them.
+    ///
+    /// Templates with a multi-trace model are detected once all their traces matched, see
+    /// `detect_multi_trace`.
     #[inline]
-    pub fn detect_gesture(&self, fragment: &mut Fragment) {
+    pub fn detect_gesture(&self, fragment: &mut Fragment, pose: &HandPose) {
+        // Clear the fragment if it was part of a detected multi-trace template
+        if self
+            .pending_clears
+            .lock()
+            .expect("failed to lock list of fragments to clear")
+            .remove(&(fragment.hand(), fragment.part()))
+        {
+            fragment.clear_detected();
+            return;
+        }
+
         // Attempt to find a matching template
-        if let Some(template) = self.store.find_matching(fragment) {
+        if let Some((template, score)) = self.store.find_matching(fragment, pose) {
             // Clear the history to prevent overlapping detections
-            fragment.clear_most();
+            fragment.clear_detected();
 
             // Add the template as detected
-            self.add_detected(template);
+            self.add_detected(Detection::Gesture {
+                template,
+                parts: vec![(fragment.part(), fragment.side())],
+                score,
+            });
+            return;
+        }
+
+        self.detect_multi_trace(fragment, pose);
+    }
+
+    /// Attempt to detect templates with a multi-trace model, of which the given `fragment`
+    /// matches one of the traces.
+    ///
+    /// Matching traces are remembered. A template is detected once every trace has matched a
+    /// different fragment, each from the hand its trace was recorded with, at the relative timing
+    /// the traces were recorded at within `config::recognition::MULTI_TRACE_WINDOW`. Either all
+    /// or none of the traces must have matched mirrored.
+    fn detect_multi_trace(&self, fragment: &mut Fragment, pose: &HandPose) {
+        let (hand, part, side) = (fragment.hand(), fragment.part(), fragment.side());
+        let timestamp = fragment.last_update();
+
+        let mut partial = self
+            .partial
+            .lock()
+            .expect("failed to lock list of partially matched templates");
+
+        // Forget traces that matched too long ago, from this sensor
+        partial.retain(|p| p.hand.0 != hand.0 || timestamp - p.end <= MULTI_TRACE_WINDOW);
+
+        for (template, index, mirrored, score) in self.store.find_partial_matches(fragment, pose) {
+            // Remember the matched trace, replacing an earlier match by this fragment
+            partial.retain(|p| {
+                !(p.template == template.id()
+                    && p.trace == index
+                    && p.hand == hand
+                    && p.part == part)
+            });
+            partial.push(PartialMatch::new(
+                &template, index, hand, part, side, mirrored, score, timestamp,
+            ));
+
+            // Detect the template if all traces have matched
+            let matched = {
+                let candidates: Vec<&PartialMatch> = partial
+                    .iter()
+                    .filter(|p| p.template == template.id())
+                    .collect();
+                let mut chosen = Vec::new();
+                if !assign_traces(template.model().traces(), &candidates, &mut chosen) {
+                    continue;
+                }
+                chosen.into_iter().cloned().collect::<Vec<_>>()
+            };
+            partial.retain(|p| p.template != template.id());
+
+            // Clear the matched fragments to prevent overlapping detections
+            fragment.clear_detected();
+            if !self.segmentation {
+                self.pending_clears
+                    .lock()
+                    .expect("failed to lock list of fragments to clear")
+                    .extend(
+                        matched
+                            .iter()
+                            .map(|p| (p.hand, p.part))
+                            .filter(|&key| key != (hand, part)),
+                    );
+            }
+
+            self.add_detected(Detection::Gesture {
+                template,
+                parts: matched.iter().map(|p| (p.part, p.side)).collect(),
+                score: combined_score(&matched),
+            });
+            return;
+        }
+    }
+
+    /// Attempt to detect a posture in the given `pose` of the hand on the given `side`, sensed at
+    /// the given `timestamp`.
+    ///
+    /// The posture the hand is holding is tracked in `held`. A posture is detected once it has
+    /// been held for its hold time, and isn't detected again until the hand releases it.
+    pub fn detect_posture(
+        &self,
+        held: &mut Option<HeldPosture>,
+        side: Option<HandSide>,
+        pose: &HandPose,
+        timestamp: i64,
+    ) {
+        // Find the posture the hand is in, release the held posture if there is none
+        let posture = match self.store.find_posture(pose) {
+            Some(posture) => posture,
+            None => {
+                *held = None;
+                return;
+            }
+        };
+
+        // Start holding the posture if it's a different one
+        if held.map_or(true, |held| held.id != posture.id()) {
+            *held = Some(HeldPosture::new(posture.id(), timestamp));
+        }
+
+        // Detect the posture once it has been held long enough
+        if let Some(held) = held {
+            if !held.detected && timestamp - held.since >= posture.hold() {
+                held.detected = true;
+                self.add_detected(Detection::Posture {
+                    template: posture,
+                    side,
+                });
+            }
         }
     }
 
+    /// Check whether traces are segmented into strokes, to only detect gestures in whole strokes.
+    #[inline]
+    pub fn segmentation(&self) -> bool {
+        self.segmentation
+    }
+
+    /// Get the profile of the noise filter to use on sensed points.
+    #[inline]
+    pub fn filter(&self) -> FilterProfile {
+        self.filter
+    }
+
+    /// Check whether fragments should maintain their rotational trace in a fitted drawing plane.
+    ///
+    /// This is only needed while templates match in a fitted plane, or while recording a template
+    /// that may be created in one.
+    #[inline]
+    pub fn tracks_fitted(&self) -> bool {
+        self.store.uses_fitted_plane() || self.state() == State::Recording
+    }
+
+    /// Get the interaction zone points are tracked in.
+    #[inline]
+    pub fn zone(&self) -> Zone {
+        self.zone
+    }
+
     /// Get the current gesture controller state.
     pub fn state(&self) -> State {
         *self
@@ -88,22 +342,22 @@ impl GestureController {
             .expect("failed to lock gesture controller state") = state;
     }
 
-    /// Add the given template to the list of detected templates.
+    /// Add the given detection to the list of detected gestures.
     /// This function also reports the detected gesture to the console.
-    fn add_detected(&self, template: Template) {
+    fn add_detected(&self, detection: Detection) {
         // Report
-        println!("-> Detected: {}", template.name());
+        println!("-> Detected: {}", detection);
 
         // TODO: do not clone here
         self.detected
             .lock()
             .expect("failed to lock list of detected gestures")
-            .push(template);
+            .push(detection);
     }
 
     /// Flush the list of detected gestures.
     /// The flushed list is returned.
-    pub fn flush_detected(&self) -> Vec<Template> {
+    pub fn flush_detected(&self) -> Vec<Detection> {
         // Create a new empty list
         let mut detected = Vec::new();
 
@@ -119,17 +373,30 @@ impl GestureController {
         detected
     }
 
-    /// Return live trace data, for visualisation.
+    /// Return live trace data along with the traced hand parts, for visualisation.
     ///
     /// TODO: this is temporary until a better method is implemented.
-    pub fn live_trace(&self) -> Vec<Model> {
+    pub fn live_trace(&self) -> Vec<(HandPart, RotTrace, Vec<Segment>)> {
         match self
             .fragment_manager
             .lock()
             .expect("failed to lock fragment manager")
             .as_ref()
         {
-            Some(manager) => manager.live_models(),
+            Some(manager) => manager.live_traces(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Return the latest sensed positions of hand parts, for visualisation.
+    pub fn live_positions(&self) -> Vec<(HandPart, Point3)> {
+        match self
+            .fragment_manager
+            .lock()
+            .expect("failed to lock fragment manager")
+            .as_ref()
+        {
+            Some(manager) => manager.live_positions(),
             None => Vec::new(),
         }
     }
@@ -155,6 +422,144 @@ impl GestureController {
     }
 }
 
+/// A posture held by a hand, tracked for posture detection.
+#[derive(Copy, Clone, Debug)]
+pub struct HeldPosture {
+    /// The ID of the posture template that is held.
+    id: u32,
+
+    /// The timestamp in microseconds the posture has been held since.
+    since: i64,
+
+    /// Whether the held posture has been detected.
+    detected: bool,
+}
+
+impl HeldPosture {
+    /// Start holding the posture with the given template `id` at the given `timestamp`.
+    fn new(id: u32, since: i64) -> Self {
+        Self {
+            id,
+            since,
+            detected: false,
+        }
+    }
+}
+
+/// A trace of a multi-trace template that matched a fragment.
+#[derive(Clone, Debug)]
+struct PartialMatch {
+    /// The ID of the template.
+    template: u32,
+
+    /// The index of the matched trace in the template model.
+    trace: usize,
+
+    /// The key of the hand the matching fragment is part of.
+    hand: HandKey,
+
+    /// The hand part the matching fragment traces.
+    part: HandPart,
+
+    /// The side of the hand the matching fragment is part of, if known.
+    side: Option<HandSide>,
+
+    /// Whether the trace matched mirrored.
+    mirrored: bool,
+
+    /// The score of the matched trace.
+    score: MatchScore,
+
+    /// The timestamp in microseconds the last trace of the template is expected to end at.
+    end: i64,
+}
+
+impl PartialMatch {
+    /// Construct a match of the trace with the given `index` in the model of the given
+    /// `template`, by a fragment of the given hand part that ended at the given `timestamp`,
+    /// scoring the given `score`.
+    ///
+    /// The end of the last trace is derived from the offset of the matched trace.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        template: &Template,
+        index: usize,
+        hand: HandKey,
+        part: HandPart,
+        side: Option<HandSide>,
+        mirrored: bool,
+        score: MatchScore,
+        timestamp: i64,
+    ) -> Self {
+        PartialMatch {
+            template: template.id(),
+            trace: index,
+            hand,
+            part,
+            side,
+            mirrored,
+            score,
+            end: timestamp + template.model().traces()[index].offset(),
+        }
+    }
+}
+
+/// Combine the scores of the given `matched` traces of a template, into the worst of them.
+///
+/// A multi-trace template is only as good a match as its worst matching trace.
+fn combined_score(matched: &[PartialMatch]) -> MatchScore {
+    let mut scores = matched.iter().map(|p| p.score.clone());
+    let first = scores
+        .next()
+        .expect("failed to combine scores, no trace matched");
+    scores.fold(first, MatchScore::worst)
+}
+
+/// Get the offset in microseconds of each recorded trace, ending at the given `ends`
+/// timestamps, relative to the last ending trace.
+fn trace_offsets(ends: &[i64]) -> Vec<i64> {
+    let end = ends.iter().cloned().max().unwrap_or(0);
+    ends.iter().map(|t| end - t).collect()
+}
+
+/// Assign a matched fragment to each of the given model `traces`, picked from the `candidates`
+/// of a single template. The assigned matches are collected in `chosen`, in trace order.
+///
+/// Each fragment is assigned once. Traces with the same hand index must be assigned fragments of
+/// the same hand, traces with a different hand index fragments of different hands. All assigned
+/// matches must be mirrored alike, and end within `config::recognition::MULTI_TRACE_WINDOW` of
+/// each other.
+///
+/// Returns `true` if all traces were assigned.
+fn assign_traces<'a>(
+    traces: &[ModelTrace],
+    candidates: &[&'a PartialMatch],
+    chosen: &mut Vec<&'a PartialMatch>,
+) -> bool {
+    let index = chosen.len();
+    if index == traces.len() {
+        return true;
+    }
+
+    for candidate in candidates.iter().filter(|c| c.trace == index) {
+        let fits = chosen.iter().zip(traces).all(|(other, trace)| {
+            (other.hand, other.part) != (candidate.hand, candidate.part)
+                && (trace.hand() == traces[index].hand()) == (other.hand == candidate.hand)
+                && other.mirrored == candidate.mirrored
+                && (other.end - candidate.end).abs() <= MULTI_TRACE_WINDOW
+        });
+        if fits {
+            chosen.push(candidate);
+            if assign_traces(traces, candidates, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
+    }
+
+    false
+}
+
 /// The state the gesture controller may be in.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum State {
@@ -208,6 +613,18 @@ impl State {
         }
     }
 
+    /// Determine whether we should truncate points older than `config::trace::MAX_AGE` from
+    /// traces.
+    ///
+    /// Traces are kept whole while recording, as a template may take any time to record.
+    #[inline]
+    pub fn should_truncate_age(&self) -> bool {
+        match self {
+            State::Recording => false,
+            _ => true,
+        }
+    }
+
     /// Determine whether we should detect gestures in incomming trace data.
     #[inline]
     pub fn should_detect(&self) -> bool {
@@ -239,3 +656,360 @@ impl Display for State {
         write!(f, "{}", self.name())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{prng::XorShiftRng, SeedableRng};
+
+    use super::*;
+    use config::posture::HOLD_TIME;
+    use synth::{Shape, Stroke};
+    use types::{FingerType, Point3, PointTrace, PoseConstraints, SideMode};
+
+    const INDEX: HandPart = HandPart::Finger(FingerType::Index);
+    const MIDDLE: HandPart = HandPart::Finger(FingerType::Middle);
+
+    /// Build a template with a trace for each of the given hand indices, that was recorded
+    /// ending at the given `ends` timestamps.
+    fn template(hands: &[usize], ends: &[i64]) -> Template {
+        let traces = hands
+            .iter()
+            .zip(trace_offsets(ends))
+            .map(|(&hand, offset)| {
+                ModelTrace::new("trace".into(), hand, None, offset, RotTrace::empty())
+            })
+            .collect();
+        Template::for_parts("template".into(), &[INDEX], Model::from_traces(traces))
+    }
+
+    /// Build a match of the trace with the given `index` in the model of the given `template`,
+    /// with a perfect score. See `PartialMatch::new`.
+    fn matched(
+        template: &Template,
+        index: usize,
+        hand: HandKey,
+        part: HandPart,
+        mirrored: bool,
+        timestamp: i64,
+    ) -> PartialMatch {
+        let score = MatchScore::new(0.0, 0, Vec::new(), 0);
+        PartialMatch::new(
+            template, index, hand, part, None, mirrored, score, timestamp,
+        )
+    }
+
+    /// Assign the given matches to the traces of the given `template`.
+    ///
+    /// Returns the hand and part of each assigned match in trace order, or `None` if not all
+    /// traces were assigned.
+    fn assign(template: &Template, matches: &[PartialMatch]) -> Option<Vec<(HandKey, HandPart)>> {
+        let candidates: Vec<&PartialMatch> = matches.iter().collect();
+        let mut chosen = Vec::new();
+        if !assign_traces(template.model().traces(), &candidates, &mut chosen) {
+            return None;
+        }
+        Some(chosen.iter().map(|p| (p.hand, p.part)).collect())
+    }
+
+    #[test]
+    fn assign_two_hands() {
+        let template = template(&[0, 1], &[1_000_000, 1_200_000]);
+        let matches = [
+            matched(&template, 1, (0, 2), INDEX, false, 5_200_000),
+            matched(&template, 0, (0, 1), INDEX, false, 5_000_000),
+        ];
+        assert_eq!(
+            assign(&template, &matches),
+            Some(vec![((0, 1), INDEX), ((0, 2), INDEX)]),
+        );
+    }
+
+    #[test]
+    fn assign_same_hand_rejected() {
+        // Traces recorded with different hands can't be matched by a single hand
+        let matches = |template: &Template| {
+            vec![
+                matched(template, 0, (0, 1), INDEX, false, 5_000_000),
+                matched(template, 1, (0, 1), MIDDLE, false, 5_000_000),
+            ]
+        };
+        let two_hands = template(&[0, 1], &[1_000_000, 1_000_000]);
+        assert_eq!(assign(&two_hands, &matches(&two_hands)), None);
+
+        // Unless the traces were recorded with a single hand
+        let one_hand = template(&[0, 0], &[1_000_000, 1_000_000]);
+        assert!(assign(&one_hand, &matches(&one_hand)).is_some());
+
+        // A single fragment can't match two traces
+        let matches = [
+            matched(&one_hand, 0, (0, 1), INDEX, false, 5_000_000),
+            matched(&one_hand, 1, (0, 1), INDEX, false, 5_000_000),
+        ];
+        assert_eq!(assign(&one_hand, &matches), None);
+    }
+
+    #[test]
+    fn assign_mixed_mirroring_rejected() {
+        let template = template(&[0, 1], &[1_000_000, 1_000_000]);
+        let matches = [
+            matched(&template, 0, (0, 1), INDEX, true, 5_000_000),
+            matched(&template, 1, (0, 2), INDEX, false, 5_000_000),
+        ];
+        assert_eq!(assign(&template, &matches), None);
+
+        // Other matches mirrored alike may still be assigned
+        let matches = [
+            matches[0].clone(),
+            matches[1].clone(),
+            matched(&template, 1, (0, 2), MIDDLE, true, 5_000_000),
+        ];
+        assert_eq!(
+            assign(&template, &matches),
+            Some(vec![((0, 1), INDEX), ((0, 2), MIDDLE)]),
+        );
+    }
+
+    #[test]
+    fn assign_out_of_window_rejected() {
+        let template = template(&[0, 1], &[1_000_000, 1_000_000]);
+        let matches = [
+            matched(&template, 0, (0, 1), INDEX, false, 5_000_000),
+            matched(
+                &template,
+                1,
+                (0, 2),
+                INDEX,
+                false,
+                5_000_000 + MULTI_TRACE_WINDOW,
+            ),
+        ];
+        assert!(assign(&template, &matches).is_some());
+
+        let matches = [
+            matches[0].clone(),
+            matched(
+                &template,
+                1,
+                (0, 2),
+                INDEX,
+                false,
+                5_000_001 + MULTI_TRACE_WINDOW,
+            ),
+        ];
+        assert_eq!(assign(&template, &matches), None);
+    }
+
+    #[test]
+    fn offsets_align_ends() {
+        // Traces are ordered by length when recorded, not by when they end
+        let ends = [2_400_000, 3_000_000, 2_700_000];
+        let template = template(&[0, 1, 2], &ends);
+        assert_eq!(
+            template
+                .model()
+                .traces()
+                .iter()
+                .map(|t| t.offset())
+                .collect::<Vec<_>>(),
+            vec![600_000, 0, 300_000],
+        );
+
+        // Replaying the traces at the recorded relative timing must expect the same end for each
+        let shift = 10_000_000;
+        let matches: Vec<PartialMatch> = ends
+            .iter()
+            .enumerate()
+            .map(|(i, end)| matched(&template, i, (0, i as i32), INDEX, false, end + shift))
+            .collect();
+        for p in &matches {
+            assert_eq!(p.end, 3_000_000 + shift);
+        }
+        assert!(assign(&template, &matches).is_some());
+    }
+
+    #[test]
+    fn combined_score_worst() {
+        let template = template(&[0, 1], &[1_000_000, 1_000_000]);
+        let mut matches = vec![
+            matched(&template, 0, (0, 1), INDEX, false, 5_000_000),
+            matched(&template, 1, (0, 2), INDEX, false, 5_000_000),
+        ];
+
+        // The template matches as well as its worst matching trace, whichever trace it is
+        matches[1].score = MatchScore::new(0.4, 1, Vec::new(), 0);
+        assert_eq!(combined_score(&matches), matches[1].score);
+        matches[0].score = MatchScore::new(0.6, 0, Vec::new(), 0);
+        assert_eq!(combined_score(&matches), matches[0].score);
+    }
+
+    #[test]
+    fn detect_mirrored_multi_trace() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let circle = |clockwise| {
+            Stroke::new(Shape::Circle {
+                radius: 55.0,
+                clockwise,
+            })
+        };
+        let square = |clockwise| {
+            Stroke::new(Shape::Polygon {
+                sides: 4,
+                side: 70.0,
+                clockwise,
+            })
+        };
+
+        // Record a clockwise circle with the left hand, and a square with the right hand
+        let mut model = |stroke: Stroke, hand, side| {
+            let trace = PointTrace::new(stroke.points(Point3::zero(), &mut rng));
+            ModelTrace::new(
+                "trace".into(),
+                hand,
+                Some(side),
+                0,
+                trace.to_rot_trace(true),
+            )
+        };
+        let model = Model::from_traces(vec![
+            model(circle(true), 0, HandSide::Left),
+            model(square(true), 1, HandSide::Right),
+        ]);
+        let template =
+            Template::for_parts("template".into(), &[INDEX], model).with_options(TemplateOptions {
+                sides: SideMode::Mirror,
+                ..TemplateOptions::default()
+            });
+        let store = Arc::new(TemplateStore::with_templates(vec![template], vec![]));
+        let controller = Arc::new(GestureController::new(
+            store,
+            false,
+            FilterProfile::None,
+            Zone::Any,
+        ));
+
+        // Perform both traces mirrored, with the other hand at the same time
+        let pose = HandPose::default();
+        let mut draw = |stroke: Stroke, hand, side| {
+            let mut fragment = Fragment::new((0, hand), INDEX, controller.clone());
+            let points = stroke.points(Point3::zero(), &mut rng);
+            let start = 2_000_000 - points.len() as i64 * 10_000;
+            for (i, point) in points.into_iter().enumerate() {
+                fragment.process_sensor_point(point, start + i as i64 * 10_000, Some(side), &pose);
+            }
+        };
+        draw(circle(false), 1, HandSide::Right);
+        draw(square(false), 2, HandSide::Left);
+
+        // Both hand parts are reported in trace order, with the side of their hand
+        let detected = controller.flush_detected();
+        assert_eq!(detected.len(), 1);
+        match &detected[0] {
+            Detection::Gesture { parts, .. } => assert_eq!(
+                parts,
+                &vec![
+                    (INDEX, Some(HandSide::Right)),
+                    (INDEX, Some(HandSide::Left))
+                ],
+            ),
+            detection => panic!("unexpected detection: {}", detection),
+        }
+    }
+
+    /// Get a hand pose with the given extended `fingers`.
+    fn extended(fingers: &[FingerType]) -> HandPose {
+        HandPose {
+            extended: fingers.to_vec(),
+            ..HandPose::default()
+        }
+    }
+
+    /// Build a gesture controller detecting a few posture templates, listing a posture of any two
+    /// fingers before a more specific one.
+    fn posture_controller() -> GestureController {
+        let postures = vec![
+            PostureTemplate::new(
+                "Any two fingers".into(),
+                None,
+                PoseConstraints {
+                    extended: Some(2),
+                    ..PoseConstraints::default()
+                },
+            ),
+            PostureTemplate::new(
+                "Peace".into(),
+                Some(vec![FingerType::Index, FingerType::Middle]),
+                PoseConstraints::default(),
+            ),
+            PostureTemplate::new(
+                "Thumbs up".into(),
+                Some(vec![FingerType::Thumb]),
+                PoseConstraints::default(),
+            ),
+        ];
+        let store = Arc::new(TemplateStore::with_templates(vec![], postures));
+        GestureController::new(store, false, FilterProfile::None, Zone::Any)
+    }
+
+    /// Get the names of the postures detected by the given `controller`.
+    fn detected_postures(controller: &GestureController) -> Vec<String> {
+        controller
+            .flush_detected()
+            .into_iter()
+            .map(|detection| match detection {
+                Detection::Posture { template, .. } => template.name().to_owned(),
+                detection => panic!("unexpected detection: {}", detection),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn posture_detected_once_held() {
+        let controller = posture_controller();
+        let pose = extended(&[FingerType::Index, FingerType::Middle]);
+        let mut held = None;
+
+        // The most specific posture is detected once it has been held for its hold time
+        for &timestamp in &[0, 500_000, 999_999] {
+            controller.detect_posture(&mut held, None, &pose, timestamp);
+        }
+        assert!(detected_postures(&controller).is_empty());
+        controller.detect_posture(&mut held, None, &pose, HOLD_TIME);
+        assert_eq!(detected_postures(&controller), vec!["Peace"]);
+
+        // It isn't detected again while held
+        for &timestamp in &[HOLD_TIME + 1, 2 * HOLD_TIME, 5 * HOLD_TIME] {
+            controller.detect_posture(&mut held, None, &pose, timestamp);
+        }
+        assert!(detected_postures(&controller).is_empty());
+
+        // Until it is released and held again
+        controller.detect_posture(&mut held, None, &HandPose::default(), 6 * HOLD_TIME);
+        controller.detect_posture(&mut held, None, &pose, 7 * HOLD_TIME);
+        controller.detect_posture(&mut held, None, &pose, 8 * HOLD_TIME);
+        assert_eq!(detected_postures(&controller), vec!["Peace"]);
+    }
+
+    #[test]
+    fn posture_change_restarts_hold() {
+        let controller = posture_controller();
+        let peace = extended(&[FingerType::Index, FingerType::Middle]);
+        let thumbs_up = extended(&[FingerType::Thumb]);
+        let mut held = None;
+
+        // Switching to another posture before the hold time restarts the timer
+        controller.detect_posture(&mut held, None, &peace, 0);
+        controller.detect_posture(&mut held, None, &peace, HOLD_TIME / 2);
+        controller.detect_posture(&mut held, None, &thumbs_up, HOLD_TIME / 2 + 1);
+        controller.detect_posture(&mut held, None, &thumbs_up, HOLD_TIME);
+        assert!(detected_postures(&controller).is_empty());
+
+        controller.detect_posture(&mut held, None, &thumbs_up, HOLD_TIME * 3 / 2 + 1);
+        assert_eq!(detected_postures(&controller), vec!["Thumbs up"]);
+
+        // A less specific posture matches other fingers
+        let pose = extended(&[FingerType::Thumb, FingerType::Pinky]);
+        controller.detect_posture(&mut held, None, &pose, 2 * HOLD_TIME);
+        controller.detect_posture(&mut held, None, &pose, 3 * HOLD_TIME);
+        assert_eq!(detected_postures(&controller), vec!["Any two fingers"]);
+    }
+}
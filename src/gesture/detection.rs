@@ -0,0 +1,59 @@
+use std::fmt::{self, Display};
+
+use types::{HandPart, HandSide, MatchScore, PostureTemplate, Template};
+
+/// A detected gesture.
+///
+/// The side of the hand that performed it is included, if known.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Detection {
+    /// A template that was matched, on the traces of the given hand parts.
+    ///
+    /// Each hand part is listed with the side of its hand, in the order of the traces of the
+    /// template model. The score describes how well the trace matched. For templates with a
+    /// multi-trace model, it is the score of the trace that was matched last.
+    Gesture {
+        template: Template,
+        parts: Vec<(HandPart, Option<HandSide>)>,
+        score: MatchScore,
+    },
+
+    /// A posture template that was held by a hand.
+    Posture {
+        template: PostureTemplate,
+        side: Option<HandSide>,
+    },
+}
+
+impl Display for Detection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Detection::Gesture {
+                template,
+                parts,
+                score,
+            } => {
+                let parts: Vec<String> = parts
+                    .iter()
+                    .map(|(part, side)| match side {
+                        Some(side) => format!("{} {}", side, part),
+                        None => part.to_string(),
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "{} ({}, distance {:.2})",
+                    template.name(),
+                    parts.join(", "),
+                    score.distance(),
+                )
+            }
+            Detection::Posture {
+                template,
+                side: Some(side),
+            } => write!(f, "{} ({} hand posture)", template.name(), side),
+            Detection::Posture { template, .. } => write!(f, "{} (posture)", template.name()),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod controller;
+pub mod detection;
 
-/// Re-export
-pub use self::controller::GestureController;
 pub use self::controller::State as GestureState;
+/// Re-export
+pub use self::controller::{GestureController, HeldPosture};
+pub use self::detection::Detection;
@@ -4,6 +4,7 @@
 extern crate clap;
 extern crate directories;
 extern crate itertools;
+#[cfg(feature = "leap")]
 extern crate leap;
 extern crate nalgebra;
 extern crate openssl_probe;
@@ -18,6 +19,7 @@ extern crate serde;
 extern crate serde_derive;
 extern crate serde_json;
 extern crate test;
+extern crate tungstenite;
 extern crate webbrowser;
 
 #[cfg(test)]
@@ -26,20 +28,27 @@ extern crate pretty_assertions;
 
 pub(crate) mod config;
 pub(crate) mod core;
+pub(crate) mod filter;
 pub(crate) mod fragment;
 pub(crate) mod gesture;
 pub(crate) mod prelude;
 pub(crate) mod sensor;
 pub(crate) mod store;
+pub(crate) mod synth;
 pub(crate) mod types;
 pub(crate) mod util;
 #[cfg(feature = "web")]
 pub(crate) mod web;
 
+use std::net::SocketAddr;
+
 use clap::{App, Arg};
 use openssl_probe::init_ssl_cert_env_vars;
 
 use core::Core;
+use filter::FilterProfile;
+use sensor::{Mount, ReplaySpeed};
+use types::Zone;
 
 fn main() {
     // Build the clap app, get the matches
@@ -60,7 +69,98 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
     let mut app = App::new(crate_name!())
         .version(crate_version!())
         .author(crate_authors!())
-        .about(crate_description!());
+        .about(crate_description!())
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Record calibrated frames of a single sensor to the given file"),
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Replay recorded sensor frames from the given file"),
+        )
+        .arg(
+            Arg::with_name("replay-speed")
+                .long("replay-speed")
+                .value_name("SPEED")
+                .takes_value(true)
+                .requires("replay")
+                .validator(|speed| speed.parse::<ReplaySpeed>().map(|_| ()))
+                .help("Replay speed factor, or 'max' to replay as fast as possible [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("demo")
+                .long("demo")
+                .conflicts_with("replay")
+                .help("Draw synthetic demo strokes instead of using a sensor"),
+        )
+        .arg(
+            Arg::with_name("segment")
+                .long("segment")
+                .help("Segment traces into strokes by pause and velocity, and only detect gestures in whole strokes"),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .long("filter")
+                .value_name("PROFILE")
+                .takes_value(true)
+                .validator(|profile| profile.parse::<FilterProfile>().map(|_| ()))
+                .help("Noise filter for sensed points: 'none', 'ema', 'one-euro' or 'kalman', tuned like 'one-euro:min-cutoff=1.5,beta=0.02' [default: none]"),
+        )
+        .arg(
+            Arg::with_name("zone")
+                .long("zone")
+                .value_name("ZONE")
+                .takes_value(true)
+                .validator(|zone| zone.parse::<Zone>().map(|_| ()))
+                .help("Only track points in an interaction zone, leaving it resets traces: 'any', 'box' or 'depth', with bounds like 'box:-150,100,-150,150,350,100' or 'depth:-20' [default: any]"),
+        )
+        .arg(
+            Arg::with_name("mount")
+                .long("mount")
+                .value_name("ORIENTATION")
+                .takes_value(true)
+                .validator(|mount| mount.parse::<Mount>().map(|_| ()))
+                .help("How sensors are mounted, used for sensors that aren't calibrated: 'desk', 'screen' or 'head' [default: desk]"),
+        )
+        .arg(
+            Arg::with_name("udp")
+                .long("udp")
+                .value_name("ADDRESS")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["replay", "demo"])
+                .validator(validate_address)
+                .help("Receive finger tip points from an external tracker over UDP on the given address, may be given multiple times"),
+        )
+        .arg(
+            Arg::with_name("websocket")
+                .long("websocket")
+                .value_name("ADDRESS")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["replay", "demo"])
+                .validator(validate_address)
+                .help("Receive finger tip points from an external tracker over a WebSocket on the given address, may be given multiple times"),
+        );
+
+    // Define the Leap Motion argument if the Leap Motion backend is enabled
+    #[cfg(feature = "leap")]
+    {
+        app = app.arg(
+            Arg::with_name("leap")
+                .long("leap")
+                .conflicts_with_all(&["replay", "demo"])
+                .help("Use the Leap Motion sensor alongside network sources"),
+        );
+    }
 
     // Define the open argument if web is enabled
     #[cfg(feature = "web")]
@@ -75,3 +175,11 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
 
     app
 }
+
+/// Validate that the given CLI argument value is a socket address.
+fn validate_address(address: String) -> Result<(), String> {
+    address
+        .parse::<SocketAddr>()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,228 @@
+use std::f64::consts::FRAC_PI_2;
+use std::str::FromStr;
+
+use nalgebra::geometry::{Point3 as NPoint3, Rotation3};
+use nalgebra::{Matrix3, Vector3};
+
+use super::Frame;
+use config::calibration::{GUIDE_DISTANCE, ORIGIN};
+use types::Point3;
+
+/// A calibration transform for a sensor.
+///
+/// This maps points from the coordinate space of a sensor into the shared coordinate space used
+/// for recognition. Points are scaled and rotated first, and translated afterwards.
+///
+/// In the shared coordinate space the X axis points to the right, the Y axis points up and the Z
+/// axis points towards the user. Gestures are drawn in the XY plane.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "CalibrationData", into = "CalibrationData")]
+pub struct Calibration {
+    /// The rotation to apply.
+    rotation: Rotation3<f64>,
+
+    /// The translation in millimeters to apply, after rotating.
+    translation: Vector3<f64>,
+
+    /// The factor to scale by.
+    scale: f64,
+}
+
+impl Calibration {
+    /// Construct a new calibration transform.
+    pub fn new(rotation: Rotation3<f64>, translation: Vector3<f64>, scale: f64) -> Self {
+        Self {
+            rotation,
+            translation,
+            scale,
+        }
+    }
+
+    /// Construct an identity calibration, which doesn't transform points at all.
+    pub fn identity() -> Self {
+        Self::new(Rotation3::identity(), Vector3::zeros(), 1.0)
+    }
+
+    /// Derive a calibration from three finger tip positions in sensor space.
+    ///
+    /// The user is guided to hold a finger tip at the `origin` of the drawing plane, then
+    /// `GUIDE_DISTANCE` to the `right` of it, and then `GUIDE_DISTANCE` `up` from it.
+    /// The resulting calibration maps the origin onto `ORIGIN`, and the drawing plane onto the XY
+    /// plane.
+    ///
+    /// If the positions are too close together to determine the axes, `None` is returned.
+    pub fn from_poses(origin: Point3, right: Point3, up: Point3) -> Option<Self> {
+        let origin = origin.to_npoint();
+        let right = right.to_npoint() - origin;
+        let up = up.to_npoint() - origin;
+
+        // Determine the orthonormal axes of the drawing plane
+        let x = right.try_normalize(1.0)?;
+        let y = (up - x * x.dot(&up)).try_normalize(1.0)?;
+        let z = x.cross(&y);
+        let rotation = Rotation3::from_matrix_unchecked(Matrix3::new(
+            x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z,
+        ));
+
+        // Scale the guided distances to the real distance, move the origin in place
+        let scale = GUIDE_DISTANCE * 2.0 / (right.norm() + up.norm());
+        let translation =
+            Vector3::new(ORIGIN[0], ORIGIN[1], ORIGIN[2]) - rotation * origin.coords * scale;
+
+        Some(Self::new(rotation, translation, scale))
+    }
+
+    /// Transform the given sensor point.
+    pub fn apply(&self, point: Point3) -> Point3 {
+        let point: NPoint3<f64> =
+            self.rotation * (point.to_npoint() * self.scale) + self.translation;
+        Point3::new(point.x, point.y, point.z)
+    }
+
+    /// Transform all finger tip points in the given sensor frame.
+    pub fn apply_frame(&self, frame: &mut Frame) {
+        for hand in &mut frame.hands {
+            hand.palm = hand.palm.map(|palm| self.apply(palm));
+            for finger in &mut hand.fingers {
+                finger.tip = self.apply(finger.tip);
+            }
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// The serialized form of a calibration.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+struct CalibrationData {
+    /// The rotation matrix, as rows.
+    rotation: [[f64; 3]; 3],
+
+    /// The translation.
+    translation: [f64; 3],
+
+    /// The scale factor.
+    scale: f64,
+}
+
+impl From<Calibration> for CalibrationData {
+    fn from(calibration: Calibration) -> Self {
+        let m = calibration.rotation.matrix();
+        let t = calibration.translation;
+        CalibrationData {
+            rotation: [
+                [m[(0, 0)], m[(0, 1)], m[(0, 2)]],
+                [m[(1, 0)], m[(1, 1)], m[(1, 2)]],
+                [m[(2, 0)], m[(2, 1)], m[(2, 2)]],
+            ],
+            translation: [t.x, t.y, t.z],
+            scale: calibration.scale,
+        }
+    }
+}
+
+impl From<CalibrationData> for Calibration {
+    fn from(data: CalibrationData) -> Self {
+        let r = data.rotation;
+        let t = data.translation;
+        Calibration::new(
+            Rotation3::from_matrix_unchecked(Matrix3::new(
+                r[0][0], r[0][1], r[0][2], r[1][0], r[1][1], r[1][2], r[2][0], r[2][1], r[2][2],
+            )),
+            Vector3::new(t[0], t[1], t[2]),
+            data.scale,
+        )
+    }
+}
+
+/// The orientation a sensor is mounted in.
+///
+/// This provides a default calibration for sensors that haven't been calibrated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Mount {
+    /// Lying on a desk facing up, drawing in front of the user above the sensor.
+    Desk,
+
+    /// Mounted on a screen facing the user, drawing in front of the sensor.
+    Screen,
+
+    /// Mounted on a headset facing away from the user, drawing in front of the sensor.
+    Head,
+}
+
+impl Mount {
+    /// Get the calibration for this mounting orientation.
+    pub fn calibration(&self) -> Calibration {
+        let rotation = match self {
+            Mount::Desk => Rotation3::identity(),
+            Mount::Screen => Rotation3::from_euler_angles(FRAC_PI_2, 0.0, 0.0),
+            Mount::Head => Rotation3::from_euler_angles(-FRAC_PI_2, 0.0, 0.0),
+        };
+        Calibration::new(rotation, Vector3::zeros(), 1.0)
+    }
+}
+
+impl Default for Mount {
+    fn default() -> Mount {
+        Mount::Desk
+    }
+}
+
+impl FromStr for Mount {
+    type Err = String;
+
+    /// Parse a mounting orientation, being `desk`, `screen` or `head`.
+    fn from_str(mount: &str) -> Result<Self, Self::Err> {
+        match mount {
+            "desk" => Ok(Mount::Desk),
+            "screen" => Ok(Mount::Screen),
+            "head" => Ok(Mount::Head),
+            _ => Err(format!(
+                "invalid mount '{}', must be 'desk', 'screen' or 'head'",
+                mount,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assert that the given points are approximately equal.
+    fn assert_near(a: Point3, b: Point3) {
+        assert!(
+            (a.to_npoint() - b.to_npoint()).norm() < 1e-6,
+            "{} is not near {}",
+            a,
+            b,
+        );
+    }
+
+    #[test]
+    fn calibrate_from_poses() {
+        // A sensor on a desk, looking at a plane tilted towards the user, in centimeters
+        let origin = Point3::new(2.0, 10.0, 5.0);
+        let right = Point3::new(12.0, 10.0, 5.0);
+        let up = Point3::new(2.0, 18.0, -1.0);
+
+        let calibration = Calibration::from_poses(origin, right, up).unwrap();
+        let target = Point3::new(ORIGIN[0], ORIGIN[1], ORIGIN[2]);
+        assert_near(calibration.apply(origin), target);
+        assert_near(
+            calibration.apply(right),
+            Point3::new(target.x + GUIDE_DISTANCE, target.y, target.z),
+        );
+        assert_near(
+            calibration.apply(up),
+            Point3::new(target.x, target.y + GUIDE_DISTANCE, target.z),
+        );
+
+        // Poses on a single line can't be calibrated with
+        assert!(Calibration::from_poses(origin, right, Point3::new(22.0, 10.0, 5.0)).is_none());
+    }
+}
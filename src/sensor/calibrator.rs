@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use super::{Calibration, Frame, Mount, SensorId};
+use config::calibration::{CAPTURE_SAMPLES, GUIDE_DISTANCE};
+use store::CalibrationStore;
+use types::{FingerType, Point3};
+
+/// The instructions for each pose the user is guided through by the calibration wizard.
+const WIZARD_STEPS: [&str; 3] = [
+    "Hold your index finger tip still at the center of where you want to draw gestures",
+    "Move your index finger tip {} cm straight to the right, and hold it still",
+    "Move your index finger tip back to the center, then {} cm straight up, and hold it still",
+];
+
+/// The sensor calibrator.
+///
+/// This keeps track of the calibration of each running sensor, restores and saves calibrations
+/// through the calibration store, and runs the calibration wizard.
+///
+/// The calibrator may be shared and used from any context, such as the web server.
+pub struct Calibrator {
+    /// The calibration store, calibrations are saved to and restored from.
+    store: Arc<CalibrationStore>,
+
+    /// The calibration used for sensors that haven't been calibrated.
+    mount: Mount,
+
+    /// The running sensors, by their ID.
+    sensors: Mutex<BTreeMap<SensorId, CalibratedSensor>>,
+
+    /// The calibration wizard, if running.
+    wizard: Mutex<Option<Wizard>>,
+}
+
+impl Calibrator {
+    /// Construct a new calibrator.
+    ///
+    /// Sensors that haven't been calibrated are calibrated for the given `mount` orientation.
+    pub fn new(store: Arc<CalibrationStore>, mount: Mount) -> Self {
+        Self {
+            store,
+            mount,
+            sensors: Mutex::new(BTreeMap::new()),
+            wizard: Mutex::new(None),
+        }
+    }
+
+    /// Register a sensor with the given ID and name.
+    ///
+    /// The calibration stored for the sensor name is restored. The returned calibration is shared
+    /// with the calibrator, and is updated when the sensor is calibrated.
+    pub fn register(&self, id: SensorId, name: String) -> Arc<RwLock<Calibration>> {
+        let calibration = Arc::new(RwLock::new(
+            self.store
+                .get(&name)
+                .unwrap_or_else(|| self.mount.calibration()),
+        ));
+        self.sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to register sensor")
+            .insert(
+                id,
+                CalibratedSensor {
+                    name,
+                    calibration: calibration.clone(),
+                },
+            );
+        calibration
+    }
+
+    /// Unregister the sensor with the given ID, stopping a wizard running for it.
+    pub fn unregister(&self, id: SensorId) {
+        self.sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to unregister sensor")
+            .remove(&id);
+
+        let mut wizard = self
+            .wizard
+            .lock()
+            .expect("failed to lock calibration wizard");
+        if wizard.as_ref().map_or(false, |w| w.sensor == id) {
+            wizard.take();
+        }
+    }
+
+    /// Get the calibration state of all registered sensors.
+    pub fn sensors(&self) -> Vec<SensorCalibration> {
+        self.sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to list sensors")
+            .iter()
+            .map(|(&id, sensor)| SensorCalibration {
+                id,
+                name: sensor.name.clone(),
+                calibration: sensor.calibration(),
+            })
+            .collect()
+    }
+
+    /// Set and save the calibration of the sensor with the given ID.
+    ///
+    /// Returns `false` if no such sensor is registered or if saving failed.
+    pub fn set(&self, id: SensorId, calibration: Calibration) -> bool {
+        let sensors = self
+            .sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to set calibration");
+        let sensor = match sensors.get(&id) {
+            Some(sensor) => sensor,
+            None => return false,
+        };
+
+        *sensor
+            .calibration
+            .write()
+            .expect("failed to lock sensor calibration for writing") = calibration;
+        self.store.set(sensor.name.clone(), calibration).is_ok()
+    }
+
+    /// Reset the calibration of the sensor with the given ID to the mounting orientation default.
+    ///
+    /// Returns `false` if no such sensor is registered or if saving failed.
+    pub fn reset(&self, id: SensorId) -> bool {
+        let sensors = self
+            .sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to reset calibration");
+        let sensor = match sensors.get(&id) {
+            Some(sensor) => sensor,
+            None => return false,
+        };
+
+        *sensor
+            .calibration
+            .write()
+            .expect("failed to lock sensor calibration for writing") = self.mount.calibration();
+        self.store.delete(&sensor.name).is_ok()
+    }
+
+    /// Observe a raw frame produced by the sensor with the given ID.
+    ///
+    /// If the calibration wizard is running for this sensor, the index finger tip position is
+    /// collected for capturing a pose.
+    pub fn observe(&self, id: SensorId, frame: &Frame) {
+        let mut wizard = self
+            .wizard
+            .lock()
+            .expect("failed to lock calibration wizard");
+        let wizard = match *wizard {
+            Some(ref mut wizard) if wizard.sensor == id => wizard,
+            _ => return,
+        };
+
+        let tip = frame
+            .hands
+            .iter()
+            .flat_map(|h| h.fingers.iter())
+            .find(|f| f.finger_type == FingerType::Index)
+            .map(|f| f.tip);
+        if let Some(tip) = tip {
+            if wizard.samples.len() >= CAPTURE_SAMPLES {
+                wizard.samples.pop_front();
+            }
+            wizard.samples.push_back(tip);
+        }
+    }
+
+    /// Start the calibration wizard for the sensor with the given ID.
+    ///
+    /// A wizard that is already running is replaced. If no such sensor is registered, `None` is
+    /// returned instead.
+    pub fn start_wizard(&self, id: SensorId) -> Option<WizardStatus> {
+        if !self
+            .sensors
+            .lock()
+            .expect("failed to lock calibrator sensors to start wizard")
+            .contains_key(&id)
+        {
+            return None;
+        }
+
+        let wizard = Wizard::new(id);
+        let status = wizard.status();
+        self.wizard
+            .lock()
+            .expect("failed to lock calibration wizard")
+            .replace(wizard);
+        Some(status)
+    }
+
+    /// Capture the current pose for the running calibration wizard.
+    ///
+    /// After the last pose, the sensor calibration is derived from the captured poses and saved,
+    /// and the wizard is stopped. The status of the wizard is returned, or `None` if it's done.
+    ///
+    /// An error is returned if no wizard is running, or if the pose couldn't be captured.
+    pub fn capture(&self) -> Result<Option<WizardStatus>, String> {
+        let mut guard = self
+            .wizard
+            .lock()
+            .expect("failed to lock calibration wizard");
+
+        // Capture the averaged finger tip position for the current pose
+        {
+            let wizard = guard.as_mut().ok_or("no calibration wizard is running")?;
+            if wizard.samples.is_empty() {
+                return Err("no index finger tip in view of the sensor".into());
+            }
+            let count = wizard.samples.len() as f64;
+            let sum = wizard.samples.drain(..).fold((0.0, 0.0, 0.0), |sum, p| {
+                (sum.0 + p.x, sum.1 + p.y, sum.2 + p.z)
+            });
+            wizard
+                .poses
+                .push(Point3::new(sum.0 / count, sum.1 / count, sum.2 / count));
+
+            if wizard.poses.len() < WIZARD_STEPS.len() {
+                return Ok(Some(wizard.status()));
+            }
+        }
+
+        // All poses are captured, derive the calibration
+        let wizard = guard.take().unwrap();
+        let calibration =
+            Calibration::from_poses(wizard.poses[0], wizard.poses[1], wizard.poses[2]).ok_or(
+                "finger tip positions too close together, try again with larger movements",
+            )?;
+        if !self.set(wizard.sensor, calibration) {
+            return Err("failed to save sensor calibration".into());
+        }
+        Ok(None)
+    }
+
+    /// Stop the running calibration wizard, without changing any calibration.
+    pub fn cancel_wizard(&self) {
+        self.wizard
+            .lock()
+            .expect("failed to lock calibration wizard")
+            .take();
+    }
+
+    /// Get the status of the running calibration wizard.
+    ///
+    /// If no wizard is running, `None` is returned.
+    pub fn wizard_status(&self) -> Option<WizardStatus> {
+        self.wizard
+            .lock()
+            .expect("failed to lock calibration wizard")
+            .as_ref()
+            .map(|wizard| wizard.status())
+    }
+}
+
+/// A sensor registered in the calibrator.
+struct CalibratedSensor {
+    /// The sensor name.
+    name: String,
+
+    /// The calibration, shared with the sensor listener.
+    calibration: Arc<RwLock<Calibration>>,
+}
+
+impl CalibratedSensor {
+    /// Get the current calibration.
+    fn calibration(&self) -> Calibration {
+        *self
+            .calibration
+            .read()
+            .expect("failed to lock sensor calibration for reading")
+    }
+}
+
+/// The calibration state of a sensor.
+#[derive(Serialize)]
+pub struct SensorCalibration {
+    /// The sensor ID.
+    pub id: SensorId,
+
+    /// The sensor name.
+    pub name: String,
+
+    /// The current calibration.
+    pub calibration: Calibration,
+}
+
+/// A running calibration wizard, guiding the user through a few finger poses.
+struct Wizard {
+    /// The ID of the sensor being calibrated.
+    sensor: SensorId,
+
+    /// The finger tip positions captured for each pose so far.
+    poses: Vec<Point3>,
+
+    /// The most recent finger tip positions for the current pose.
+    samples: VecDeque<Point3>,
+}
+
+impl Wizard {
+    /// Construct a new wizard for the sensor with the given ID.
+    fn new(sensor: SensorId) -> Self {
+        Self {
+            sensor,
+            poses: Vec::with_capacity(WIZARD_STEPS.len()),
+            samples: VecDeque::with_capacity(CAPTURE_SAMPLES),
+        }
+    }
+
+    /// Get the status of this wizard.
+    fn status(&self) -> WizardStatus {
+        let step = self.poses.len();
+        WizardStatus {
+            sensor: self.sensor,
+            step,
+            steps: WIZARD_STEPS.len(),
+            instruction: WIZARD_STEPS[step].replace("{}", &(GUIDE_DISTANCE / 10.0).to_string()),
+        }
+    }
+}
+
+/// The status of a running calibration wizard.
+#[derive(Serialize)]
+pub struct WizardStatus {
+    /// The ID of the sensor being calibrated.
+    pub sensor: SensorId,
+
+    /// The index of the current step.
+    pub step: usize,
+
+    /// The total number of steps.
+    pub steps: usize,
+
+    /// The instruction for the user for the current step.
+    pub instruction: String,
+}
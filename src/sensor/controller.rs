@@ -1,15 +1,31 @@
+use std::collections::BTreeMap;
+use std::io::Result;
 use std::sync::Arc;
 
 use fragment::FragmentManager;
 
-use super::sensor::{Sensor, SensorListener};
+use super::sensor::{Sensor, SensorId, SensorListener};
+use super::{Calibrator, FrameRecorder, SensorSource, StatusMonitor};
 
 /// This thing should manage controllers, fethcing points
+///
+/// Multiple sensors may be running at the same time, each with its own calibration managed by the
+/// calibrator. Hands are tracked separately for each sensor.
 pub struct SensorController {
-    /// The leap motion sensor.
-    ///
-    /// This will be extended in the future for supporting multiple sensors
-    _sensor: Sensor,
+    /// The fragment manager, passed to the listener of started sensors.
+    fragment_manager: Arc<FragmentManager>,
+
+    /// The calibrator, managing the calibration of started sensors.
+    calibrator: Arc<Calibrator>,
+
+    /// The status monitor, tracking the status of started sensors.
+    monitor: Arc<StatusMonitor>,
+
+    /// The running sensors, by their ID.
+    sensors: BTreeMap<SensorId, Sensor>,
+
+    /// The ID to give the next added sensor.
+    next_id: SensorId,
 }
 
 impl SensorController {
@@ -17,9 +33,132 @@ impl SensorController {
     ///
     /// As the sensor might use the fragment manager for tracking data,
     /// a refrence to it must be given.
-    pub fn new(fragment_manager: Arc<FragmentManager>) -> Self {
+    pub fn new(
+        fragment_manager: Arc<FragmentManager>,
+        calibrator: Arc<Calibrator>,
+        monitor: Arc<StatusMonitor>,
+    ) -> Self {
         Self {
-            _sensor: Sensor::new(SensorListener::new(fragment_manager)),
+            fragment_manager,
+            calibrator,
+            monitor,
+            sensors: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Add and start a sensor, driven by the given sensor source.
+    ///
+    /// The sensor is registered with the calibrator, restoring its calibration, and with the
+    /// status monitor.
+    /// If a `recorder` is given, all frames produced by the sensor are recorded with it, after
+    /// calibration.
+    ///
+    /// The ID of the added sensor is returned.
+    pub fn add(
+        &mut self,
+        source: Box<dyn SensorSource>,
+        recorder: Option<FrameRecorder>,
+    ) -> Result<SensorId> {
+        let id = self.next_id;
+        let calibration = self.calibrator.register(id, source.name());
+        let status = self.monitor.register(id, source.name());
+        let listener = SensorListener::new(
+            id,
+            self.fragment_manager.clone(),
+            self.calibrator.clone(),
+            calibration,
+            status,
+            recorder,
+        );
+        let sensor = match Sensor::start(source, listener) {
+            Ok(sensor) => sensor,
+            Err(err) => {
+                self.calibrator.unregister(id);
+                self.monitor.unregister(id);
+                return Err(err);
+            }
+        };
+        self.sensors.insert(id, sensor);
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    /// Stop and remove the sensor with the given ID.
+    ///
+    /// All hands tracked by the sensor are dropped. Returns `false` if no such sensor exists.
+    pub fn remove(&mut self, id: SensorId) -> bool {
+        match self.sensors.remove(&id) {
+            Some(mut sensor) => {
+                sensor.stop();
+                self.calibrator.unregister(id);
+                self.monitor.unregister(id);
+                self.fragment_manager.remove_sensor_hands(id);
+                true
+            }
+            None => false,
         }
     }
+
+    /// Get the IDs of all running sensors.
+    pub fn ids(&self) -> Vec<SensorId> {
+        self.sensors.keys().cloned().collect()
+    }
+
+    /// Block until all running sensors have stopped producing frames.
+    ///
+    /// Returns immediately if no sensor is running.
+    pub fn wait(&mut self) {
+        for sensor in self.sensors.values_mut() {
+            sensor.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener, UdpSocket};
+    use std::sync::Arc;
+
+    use super::*;
+    use gesture::GestureController;
+    use sensor::{Mount, UdpSource, WebSocketSource};
+    use store::{CalibrationStore, TemplateStore};
+
+    #[test]
+    fn remove_network_sensors() {
+        let gesture_controller = Arc::new(GestureController::new(
+            Arc::new(TemplateStore::new()),
+            false,
+            Default::default(),
+            Default::default(),
+        ));
+        let mut controller = SensorController::new(
+            Arc::new(FragmentManager::new(gesture_controller)),
+            Arc::new(Calibrator::new(
+                Arc::new(CalibrationStore::new()),
+                Mount::default(),
+            )),
+            Arc::new(StatusMonitor::new()),
+        );
+
+        // Pick free addresses, by letting the system assign ports
+        let any: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let udp = UdpSocket::bind(any).unwrap().local_addr().unwrap();
+        let websocket = TcpListener::bind(any).unwrap().local_addr().unwrap();
+
+        // Removing sensors stops their threads, releasing the addresses while no data arrives
+        let udp_id = controller.add(Box::new(UdpSource::new(udp)), None).unwrap();
+        let websocket_id = controller
+            .add(Box::new(WebSocketSource::new(websocket)), None)
+            .unwrap();
+        assert_eq!(controller.ids(), vec![udp_id, websocket_id]);
+
+        assert!(controller.remove(udp_id));
+        assert!(controller.remove(websocket_id));
+        assert!(!controller.remove(udp_id));
+        assert!(controller.ids().is_empty());
+        UdpSocket::bind(udp).expect("UDP address still bound after removing sensor");
+        TcpListener::bind(websocket).expect("WebSocket address still bound after removing sensor");
+    }
 }
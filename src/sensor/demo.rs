@@ -0,0 +1,122 @@
+use std::{
+    f64::consts::PI,
+    io::Result,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use rand::thread_rng;
+
+use super::{Frame, SensorListener, SensorSource};
+use config::synth::FRAME_RATE;
+use synth::{Shape, Stroke};
+use types::Point3;
+
+/// The ID of the hand drawing demo strokes.
+const DEMO_HAND: i32 = 1;
+
+/// The pause between drawing demo strokes in milliseconds.
+const PAUSE: u64 = 1000;
+
+/// A demo sensor source.
+///
+/// This endlessly draws a set of synthetic strokes in real-time, for demonstrating gesture
+/// recognition without a physical sensor.
+pub struct DemoSource {
+    /// The drawing thread, available once started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DemoSource {
+    /// Construct a new demo source.
+    pub fn new() -> Self {
+        DemoSource { thread: None }
+    }
+}
+
+impl SensorSource for DemoSource {
+    fn name(&self) -> String {
+        "demo".into()
+    }
+
+    fn start(&mut self, mut listener: SensorListener) -> Result<()> {
+        self.thread.replace(thread::spawn(move || {
+            let mut rng = thread_rng();
+            let interval = Duration::from_micros((1_000_000.0 / FRAME_RATE) as u64);
+            let mut timestamp = 0;
+
+            listener.on_connect();
+
+            // Stop drawing as soon as the listener isn't active anymore, checked every frame
+            'draw: loop {
+                for stroke in demo_strokes() {
+                    // Draw the stroke in real-time
+                    for frame in
+                        stroke.frames(DEMO_HAND, Point3::new(0.0, 200.0, 0.0), timestamp, &mut rng)
+                    {
+                        if !listener.is_active() {
+                            break 'draw;
+                        }
+                        timestamp = frame.timestamp;
+                        listener.on_frame(frame);
+                        thread::sleep(interval);
+                    }
+
+                    // Move the hand out of view for a while between strokes
+                    listener.on_frame(Frame::new(timestamp, vec![]));
+                    let pause = Instant::now();
+                    while pause.elapsed() < Duration::from_millis(PAUSE) {
+                        if !listener.is_active() {
+                            break 'draw;
+                        }
+                        thread::sleep(interval);
+                    }
+                    timestamp += PAUSE as i64 * 1000;
+                }
+            }
+
+            listener.on_disconnect();
+        }));
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("failed to join demo thread");
+        }
+    }
+}
+
+/// The list of strokes that are drawn in the demo.
+fn demo_strokes() -> Vec<Stroke> {
+    vec![
+        Stroke::new(Shape::Circle {
+            radius: 55.0,
+            clockwise: true,
+        }),
+        Stroke::new(Shape::Polygon {
+            sides: 3,
+            side: 120.0,
+            clockwise: false,
+        })
+        .rotation(PI / 6.0),
+        Stroke::new(Shape::Circle {
+            radius: 55.0,
+            clockwise: false,
+        }),
+        Stroke::new(Shape::Polygon {
+            sides: 4,
+            side: 120.0,
+            clockwise: true,
+        }),
+        Stroke::new(Shape::Line { length: 450.0 }),
+    ]
+    .into_iter()
+    .map(|stroke| stroke.jitter(0.5).speed(250.0, 0.2))
+    .collect()
+}
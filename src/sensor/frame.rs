@@ -0,0 +1,106 @@
+use types::{FingerType, HandSide, Point3};
+
+/// A frame produced by a sensor source.
+///
+/// This holds all hands that were in view of the sensor at a single moment in time, in types
+/// owned by this crate so any sensor source can drive the recognition pipeline.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Frame {
+    /// The frame timestamp in microseconds, as reported by the sensor.
+    pub timestamp: i64,
+
+    /// The hands in view.
+    pub hands: Vec<HandFrame>,
+}
+
+impl Frame {
+    /// Construct a new frame.
+    pub fn new(timestamp: i64, hands: Vec<HandFrame>) -> Self {
+        Self { timestamp, hands }
+    }
+}
+
+/// A hand in a sensor frame.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HandFrame {
+    /// The hand ID given by the sensor.
+    ///
+    /// This ID stays the same for as long as the sensor keeps tracking the hand.
+    pub id: i32,
+
+    /// The side of the hand, if known by the sensor.
+    #[serde(default)]
+    pub side: Option<HandSide>,
+
+    /// The stabilized position of the palm center, if tracked by the sensor.
+    #[serde(default)]
+    pub palm: Option<Point3>,
+
+    /// The pinch strength, from `0` for an open hand up to `1` for a full pinch.
+    #[serde(default)]
+    pub pinch: f64,
+
+    /// The grab strength, from `0` for an open hand up to `1` for a fist.
+    #[serde(default)]
+    pub grab: f64,
+
+    /// The fingers on this hand.
+    pub fingers: Vec<FingerFrame>,
+}
+
+impl HandFrame {
+    /// Construct a new hand frame of an unknown side, without a palm position and an open hand.
+    pub fn new(id: i32, fingers: Vec<FingerFrame>) -> Self {
+        Self {
+            id,
+            side: None,
+            palm: None,
+            pinch: 0.0,
+            grab: 0.0,
+            fingers,
+        }
+    }
+
+    /// Set the side of the hand.
+    pub fn with_side(mut self, side: HandSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Set the position of the palm center.
+    pub fn with_palm(mut self, palm: Point3) -> Self {
+        self.palm = Some(palm);
+        self
+    }
+
+    /// Set the pinch and grab strengths.
+    pub fn with_strengths(mut self, pinch: f64, grab: f64) -> Self {
+        self.pinch = pinch;
+        self.grab = grab;
+        self
+    }
+}
+
+/// A finger in a sensor frame.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FingerFrame {
+    /// The type of finger.
+    pub finger_type: FingerType,
+
+    /// Whether the finger is extended.
+    pub extended: bool,
+
+    /// The stabilized position of the finger tip.
+    pub tip: Point3,
+}
+
+impl FingerFrame {
+    /// Construct a new finger frame.
+    pub fn new(finger_type: FingerType, extended: bool, tip: Point3) -> Self {
+        Self {
+            finger_type,
+            extended,
+            tip,
+        }
+    }
+}
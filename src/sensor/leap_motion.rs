@@ -0,0 +1,143 @@
+use std::{
+    io::Result,
+    sync::mpsc::{channel, Sender},
+    thread::{self, JoinHandle},
+};
+
+use leap::{
+    Controller as LeapController, Finger as LeapFinger, FingerType as LeapFingerType,
+    Frame as LeapFrame, Hand as LeapHand, Listener as LeapListener,
+};
+
+use super::{FingerFrame, Frame, HandFrame, SensorListener, SensorSource};
+use types::{FingerType, HandSide, Point3};
+
+/// A Leap Motion sensor source.
+///
+/// The Leap Motion controller is owned by a dedicated thread, which creates and drops it. The
+/// bindings make no thread safety claims for the controller, so it never leaves that thread.
+pub struct LeapMotionSource {
+    /// The sender to stop the controller thread with, by dropping it. Available once started.
+    stop: Option<Sender<()>>,
+
+    /// The controller thread, available once started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LeapMotionSource {
+    /// Construct a new Leap Motion source.
+    pub fn new() -> Self {
+        Self {
+            stop: None,
+            thread: None,
+        }
+    }
+}
+
+impl SensorSource for LeapMotionSource {
+    fn name(&self) -> String {
+        "leap-motion".into()
+    }
+
+    fn start(&mut self, listener: SensorListener) -> Result<()> {
+        let (stop, stopped) = channel::<()>();
+        self.stop.replace(stop);
+        self.thread.replace(thread::spawn(move || {
+            let _controller = LeapController::with_listener(LeapMotionListener {
+                listener,
+                last_frame_id: None,
+            });
+
+            // Keep the controller until the source is stopped, which drops the sender
+            while stopped.recv().is_ok() {}
+        }));
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("failed to join Leap Motion thread");
+        }
+    }
+}
+
+/// A Leap Motion listener, that converts Leap Motion frames and passes them to our own listener.
+struct LeapMotionListener {
+    /// The sensor listener frames are passed to.
+    listener: SensorListener,
+
+    /// The ID of the last Leap Motion frame, used to detect dropped frames.
+    last_frame_id: Option<i64>,
+}
+
+impl LeapListener for LeapMotionListener {
+    fn on_frame(&mut self, controller: &LeapController) {
+        let frame = controller.frame();
+
+        // Frame IDs are sequential, report frames that were skipped
+        let id = frame.id();
+        if let Some(last) = self.last_frame_id {
+            if id > last + 1 {
+                self.listener.on_frames_dropped((id - last - 1) as u64);
+            }
+        }
+        self.last_frame_id = Some(id);
+
+        self.listener.on_frame(convert_frame(&frame));
+    }
+
+    fn on_connect(&mut self, _: &LeapController) {
+        self.listener.on_connect();
+    }
+
+    fn on_disconnect(&mut self, _: &LeapController) {
+        self.listener.on_disconnect();
+    }
+}
+
+/// Convert a Leap Motion frame into our own frame type.
+fn convert_frame(frame: &LeapFrame) -> Frame {
+    Frame::new(
+        frame.timestamp(),
+        frame.hands().iter().map(|h| convert_hand(&h)).collect(),
+    )
+}
+
+/// Convert a Leap Motion hand into our own hand frame type.
+fn convert_hand(hand: &LeapHand) -> HandFrame {
+    HandFrame::new(
+        hand.id(),
+        hand.fingers().iter().map(|f| convert_finger(&f)).collect(),
+    )
+    .with_side(if hand.is_left() {
+        HandSide::Left
+    } else {
+        HandSide::Right
+    })
+    .with_palm(Point3::from(hand.stabilized_palm_position()))
+    .with_strengths(
+        f64::from(hand.pinch_strength()),
+        f64::from(hand.grab_strength()),
+    )
+}
+
+/// Convert a Leap Motion finger into our own finger frame type.
+fn convert_finger(finger: &LeapFinger) -> FingerFrame {
+    FingerFrame::new(
+        convert_finger_type(finger.type_enum()),
+        finger.is_extended(),
+        Point3::from(finger.stabilized_tip_position()),
+    )
+}
+
+/// Convert a Leap Motion finger type into our own finger type.
+fn convert_finger_type(finger_type: LeapFingerType) -> FingerType {
+    match finger_type {
+        LeapFingerType::Thumb => FingerType::Thumb,
+        LeapFingerType::Index => FingerType::Index,
+        LeapFingerType::Middle => FingerType::Middle,
+        LeapFingerType::Ring => FingerType::Ring,
+        LeapFingerType::Pinky => FingerType::Pinky,
+    }
+}
@@ -1,6 +1,28 @@
+pub mod calibration;
+pub mod calibrator;
 pub mod controller;
+pub mod demo;
+pub mod frame;
+#[cfg(feature = "leap")]
+pub mod leap_motion;
+pub mod network;
+pub mod record;
+pub mod replay;
 pub mod sensor;
+pub mod source;
+pub mod status;
 
 /// Re-export
+pub use self::calibration::{Calibration, Mount};
+pub use self::calibrator::Calibrator;
 pub use self::controller::SensorController;
-pub use self::sensor::Sensor;
+pub use self::demo::DemoSource;
+pub use self::frame::{FingerFrame, Frame, HandFrame};
+#[cfg(feature = "leap")]
+pub use self::leap_motion::LeapMotionSource;
+pub use self::network::{UdpSource, WebSocketSource};
+pub use self::record::{FrameReader, FrameRecorder};
+pub use self::replay::{ReplaySource, ReplaySpeed};
+pub use self::sensor::{Sensor, SensorId, SensorListener};
+pub use self::source::SensorSource;
+pub use self::status::StatusMonitor;
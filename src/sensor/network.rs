@@ -0,0 +1,436 @@
+//! Network sensor sources, receiving finger tip points from external trackers.
+//!
+//! This allows any tracker, such as a camera pipeline or VR controllers, to drive gesture
+//! recognition. Points are received over UDP or a WebSocket using a simple text protocol.
+//!
+//! Each UDP datagram or WebSocket message holds a single frame. A frame has one line for each
+//! tracked finger tip or palm, with whitespace separated fields:
+//!
+//! ```text
+//! <hand> <finger> <x> <y> <z>
+//! ```
+//!
+//! - `hand`: an integer hand ID, which must stay the same while the hand is being tracked.
+//! - `finger`: the finger ID, from `0` for the thumb up to `4` for the pinky, or `palm` for the
+//!   palm center.
+//! - `x`, `y`, `z`: the position in millimeters, `y` pointing up.
+//!
+//! The pinch and grab strength of a hand may be sent on lines with a single value, from `0` for
+//! an open hand up to `1` for a full pinch or fist:
+//!
+//! ```text
+//! <hand> pinch <strength>
+//! <hand> grab <strength>
+//! ```
+//!
+//! The side of a hand may be sent on a line with `left` or `right`, and is unknown otherwise:
+//!
+//! ```text
+//! <hand> side <left|right>
+//! ```
+//!
+//! Every finger sent is considered extended. Empty lines are ignored, and a message without any
+//! fingers reports that no hands are in view. Frames are timestamped when received.
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind, Result},
+    net::{SocketAddr, TcpListener, TcpStream, UdpSocket},
+    str,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use tungstenite::{
+    handshake::HandshakeError, server::accept, Error as WsError, Message, WebSocket,
+};
+
+use super::{FingerFrame, Frame, HandFrame, SensorListener, SensorSource};
+use config::network::POLL_INTERVAL;
+use types::{FingerType, HandSide, Point3};
+
+/// The maximum size in bytes of a UDP datagram that is received.
+const MAX_DATAGRAM_SIZE: usize = 65_536;
+
+/// A sensor source receiving frames as UDP datagrams.
+pub struct UdpSource {
+    /// The address to bind to.
+    address: SocketAddr,
+
+    /// The receiving thread, available once started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl UdpSource {
+    /// Construct a new UDP source, that will receive datagrams on the given `address`.
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            thread: None,
+        }
+    }
+}
+
+impl SensorSource for UdpSource {
+    fn name(&self) -> String {
+        format!("udp:{}", self.address)
+    }
+
+    fn start(&mut self, mut listener: SensorListener) -> Result<()> {
+        // Bind here, to report errors early
+        // Time out receiving regularly, to stop once the listener isn't active anymore
+        let socket = UdpSocket::bind(self.address)?;
+        socket.set_read_timeout(Some(Duration::from_millis(POLL_INTERVAL)))?;
+        let start = Instant::now();
+
+        self.thread.replace(thread::spawn(move || {
+            let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+
+            listener.on_connect();
+
+            while listener.is_active() {
+                let size = match socket.recv(&mut buf) {
+                    Ok(size) => size,
+                    Err(ref err) if is_timeout(err) => continue,
+                    Err(err) => {
+                        eprintln!("Failed to receive UDP frame, stopping source: {}", err);
+                        break;
+                    }
+                };
+
+                match str::from_utf8(&buf[..size]) {
+                    Ok(message) => process_message(&mut listener, message, start),
+                    Err(err) => {
+                        eprintln!("Ignoring invalid UDP frame: {}", err);
+                        listener.on_frames_dropped(1);
+                    }
+                }
+            }
+
+            listener.on_disconnect();
+        }));
+
+        Ok(())
+    }
+
+    /// Stop receiving, and wait for the receiving thread to end to release the socket.
+    fn stop(&mut self) {
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("failed to join UDP thread");
+        }
+    }
+}
+
+/// A sensor source receiving frames as WebSocket messages.
+///
+/// A single client is served at a time, other clients wait until it disconnects.
+pub struct WebSocketSource {
+    /// The address to listen on.
+    address: SocketAddr,
+
+    /// The serving thread, available once started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WebSocketSource {
+    /// Construct a new WebSocket source, that will listen for clients on the given `address`.
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            thread: None,
+        }
+    }
+}
+
+impl SensorSource for WebSocketSource {
+    fn name(&self) -> String {
+        format!("websocket:{}", self.address)
+    }
+
+    fn start(&mut self, mut listener: SensorListener) -> Result<()> {
+        // Bind here, to report errors early
+        // Poll for clients, to stop once the listener isn't active anymore
+        let server = TcpListener::bind(self.address)?;
+        server.set_nonblocking(true)?;
+        let start = Instant::now();
+
+        self.thread.replace(thread::spawn(move || {
+            while listener.is_active() {
+                let stream = match server.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(ref err) if is_timeout(err) => {
+                        thread::sleep(Duration::from_millis(POLL_INTERVAL));
+                        continue;
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to accept WebSocket client: {}", err);
+                        continue;
+                    }
+                };
+
+                let mut socket = match accept_client(stream, &listener) {
+                    Some(socket) => socket,
+                    None => continue,
+                };
+
+                listener.on_connect();
+
+                // Process messages until the client disconnects
+                while listener.is_active() {
+                    match socket.read_message() {
+                        Err(WsError::Io(ref err)) if is_timeout(err) => {}
+                        Ok(Message::Text(message)) => {
+                            process_message(&mut listener, &message, start)
+                        }
+                        Ok(Message::Binary(data)) => match str::from_utf8(&data) {
+                            Ok(message) => process_message(&mut listener, message, start),
+                            Err(err) => {
+                                eprintln!("Ignoring invalid WebSocket frame: {}", err);
+                                listener.on_frames_dropped(1);
+                            }
+                        },
+                        Ok(Message::Close(_)) | Err(_) => break,
+                        Ok(_) => {}
+                    }
+                }
+
+                // The client is gone, so are its hands
+                listener.on_disconnect();
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Stop serving, and wait for the serving thread to end to release the socket.
+    fn stop(&mut self) {
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("failed to join WebSocket thread");
+        }
+    }
+}
+
+/// Accept the WebSocket client connected through the given `stream`.
+///
+/// Reading from the client times out regularly, so the handshake is resumed until it completes
+/// or the listener isn't active anymore. If the client can't be accepted, `None` is returned.
+fn accept_client(stream: TcpStream, listener: &SensorListener) -> Option<WebSocket<TcpStream>> {
+    let configured = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(Duration::from_millis(POLL_INTERVAL))));
+    if let Err(err) = configured {
+        eprintln!("Failed to accept WebSocket client: {}", err);
+        return None;
+    }
+
+    let mut handshake = accept(stream);
+    loop {
+        match handshake {
+            Ok(socket) => return Some(socket),
+            Err(HandshakeError::Interrupted(mid)) => {
+                if !listener.is_active() {
+                    return None;
+                }
+                handshake = mid.handshake();
+            }
+            Err(HandshakeError::Failure(err)) => {
+                eprintln!("Failed to accept WebSocket client: {}", err);
+                return None;
+            }
+        }
+    }
+}
+
+/// Check whether the given error is caused by a socket operation timing out, or by a
+/// non-blocking socket having nothing to do.
+fn is_timeout(err: &Error) -> bool {
+    err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut
+}
+
+/// Parse the given frame `message`, and pass the frame to the listener.
+///
+/// Invalid messages are reported and dropped.
+fn process_message(listener: &mut SensorListener, message: &str, start: Instant) {
+    match parse_message(message, timestamp(start)) {
+        Ok(frame) => listener.on_frame(frame),
+        Err(err) => {
+            eprintln!("Ignoring invalid network frame: {}", err);
+            listener.on_frames_dropped(1);
+        }
+    }
+}
+
+/// Get the current timestamp in microseconds, relative to the given `start` time.
+fn timestamp(start: Instant) -> i64 {
+    let elapsed = start.elapsed();
+    elapsed.as_secs() as i64 * 1_000_000 + i64::from(elapsed.subsec_micros())
+}
+
+/// Parse a frame message, as described in the module documentation.
+///
+/// Fingers are grouped by their hand, in the order the hands first appear in the message.
+fn parse_message(message: &str, timestamp: i64) -> Result<Frame> {
+    let mut hands: Vec<HandFrame> = Vec::new();
+    let mut hand_index = HashMap::new();
+
+    for line in message.lines().filter(|line| !line.trim().is_empty()) {
+        let (hand_id, value) = parse_line(line)?;
+
+        let index = *hand_index.entry(hand_id).or_insert_with(|| {
+            hands.push(HandFrame::new(hand_id, vec![]));
+            hands.len() - 1
+        });
+        match value {
+            LineValue::Finger(finger) => hands[index].fingers.push(finger),
+            LineValue::Palm(palm) => hands[index].palm = Some(palm),
+            LineValue::Pinch(pinch) => hands[index].pinch = pinch,
+            LineValue::Grab(grab) => hands[index].grab = grab,
+            LineValue::Side(side) => hands[index].side = Some(side),
+        }
+    }
+
+    Ok(Frame::new(timestamp, hands))
+}
+
+/// A value parsed from a single line of a frame message.
+enum LineValue {
+    /// A finger tip.
+    Finger(FingerFrame),
+
+    /// The palm center.
+    Palm(Point3),
+
+    /// The pinch strength.
+    Pinch(f64),
+
+    /// The grab strength.
+    Grab(f64),
+
+    /// The hand side.
+    Side(HandSide),
+}
+
+/// Parse a single line from a frame message, returning the hand ID and the value.
+fn parse_line(line: &str) -> Result<(i32, LineValue)> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid finger line '{}'", line.trim()),
+        )
+    };
+
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 2 {
+        return Err(invalid());
+    }
+    let hand_id = fields[0].parse().map_err(|_| invalid())?;
+
+    // Parse side lines
+    if fields[1] == "side" {
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        return Ok((
+            hand_id,
+            LineValue::Side(fields[2].parse().map_err(|_| invalid())?),
+        ));
+    }
+
+    // Parse strength lines
+    if fields[1] == "pinch" || fields[1] == "grab" {
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let strength = fields[2].parse().map_err(|_| invalid())?;
+        return Ok((
+            hand_id,
+            if fields[1] == "pinch" {
+                LineValue::Pinch(strength)
+            } else {
+                LineValue::Grab(strength)
+            },
+        ));
+    }
+
+    if fields.len() != 5 {
+        return Err(invalid());
+    }
+    let mut coords = [0.0; 3];
+    for (coord, field) in coords.iter_mut().zip(&fields[2..]) {
+        *coord = field.parse().map_err(|_| invalid())?;
+    }
+    let point = Point3::new(coords[0], coords[1], coords[2]);
+
+    if fields[1] == "palm" {
+        return Ok((hand_id, LineValue::Palm(point)));
+    }
+
+    let finger_type = fields[1]
+        .parse()
+        .ok()
+        .and_then(FingerType::from_id)
+        .ok_or_else(invalid)?;
+
+    Ok((
+        hand_id,
+        LineValue::Finger(FingerFrame::new(finger_type, true, point)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_frame_message() {
+        let message = "3 1 10 200.5 -4\n\n7 0 1 2 3\n3 2 -1.5 0 0\n3 palm 0 150 20\n3 pinch 0.9\n3 side left\n";
+        assert_eq!(
+            parse_message(message, 42).unwrap(),
+            Frame::new(
+                42,
+                vec![
+                    HandFrame::new(
+                        3,
+                        vec![
+                            FingerFrame::new(
+                                FingerType::Index,
+                                true,
+                                Point3::new(10.0, 200.5, -4.0)
+                            ),
+                            FingerFrame::new(FingerType::Middle, true, Point3::new(-1.5, 0.0, 0.0)),
+                        ],
+                    )
+                    .with_side(HandSide::Left)
+                    .with_palm(Point3::new(0.0, 150.0, 20.0))
+                    .with_strengths(0.9, 0.0),
+                    HandFrame::new(
+                        7,
+                        vec![FingerFrame::new(
+                            FingerType::Thumb,
+                            true,
+                            Point3::new(1.0, 2.0, 3.0)
+                        )],
+                    ),
+                ],
+            ),
+        );
+        assert_eq!(parse_message("", 0).unwrap(), Frame::new(0, vec![]));
+    }
+
+    #[test]
+    fn parse_invalid_message() {
+        assert!(parse_message("1 5 0 0 0", 0).is_err());
+        assert!(parse_message("1 1 0 0", 0).is_err());
+        assert!(parse_message("1 1 0 0 x", 0).is_err());
+        assert!(parse_message("1 grab 0 0", 0).is_err());
+        assert!(parse_message("1 side up", 0).is_err());
+    }
+}
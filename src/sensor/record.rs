@@ -0,0 +1,181 @@
+//! Recording of calibrated sensor frames to disk.
+//!
+//! Frames are written to a line-oriented file. The first line holds a header identifying the
+//! format and its version, every following line holds a single JSON serialized frame.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, LineWriter, Lines, Result, Write};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json;
+
+use super::Frame;
+
+/// The format name written in the header of recorded frame files.
+pub const FORMAT_NAME: &str = "cant-touch-this-frames";
+
+/// The version of the recorded frame file format.
+///
+/// This must be increased whenever the frame structure changes in an incompatible way.
+/// Version `1` files hold raw frames, which can't be replayed as their calibration is unknown.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The header on the first line of a recorded frame file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Header {
+    /// The format name, should be `FORMAT_NAME`.
+    format: String,
+
+    /// The format version.
+    version: u32,
+}
+
+impl Header {
+    /// Construct a header for the current format version.
+    pub fn current() -> Self {
+        Header {
+            format: FORMAT_NAME.into(),
+            version: FORMAT_VERSION,
+        }
+    }
+}
+
+/// A recorder writing sensor frames to a file.
+///
+/// Frames are recorded after calibration, so replaying them doesn't depend on the calibration of
+/// the recorded sensor.
+pub struct FrameRecorder {
+    /// The writer for the recording file, flushed on each written frame line.
+    writer: LineWriter<File>,
+}
+
+impl FrameRecorder {
+    /// Create a new recorder, writing to a new file at the given `path`.
+    ///
+    /// If the file already exists, it is truncated.
+    pub fn create(path: &Path) -> Result<Self> {
+        let mut recorder = FrameRecorder {
+            writer: LineWriter::new(File::create(path)?),
+        };
+        recorder.write_line(&Header::current())?;
+        Ok(recorder)
+    }
+
+    /// Record the given frame.
+    pub fn record(&mut self, frame: &Frame) -> Result<()> {
+        self.write_line(frame)
+    }
+
+    /// Serialize the given `value`, and write it to the file as a single line.
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        let line =
+            serde_json::to_string(value).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        writeln!(self.writer, "{}", line)
+    }
+}
+
+/// A reader for recorded frame files, iterating over all frames in it.
+pub struct FrameReader {
+    /// The lines in the recorded frame file, after the header.
+    lines: Lines<BufReader<File>>,
+}
+
+impl FrameReader {
+    /// Open the recorded frame file at the given `path`.
+    ///
+    /// This fails if the file header doesn't match the current format and version.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+
+        // Read and verify the header
+        let header: Header = match lines.next() {
+            Some(line) => parse_line(&line?)?,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "missing frame file header",
+                ))
+            }
+        };
+        if header.format != FORMAT_NAME || header.version != FORMAT_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "unsupported frame file format '{}' version {}, expected version {}",
+                    header.format, header.version, FORMAT_VERSION,
+                ),
+            ));
+        }
+
+        Ok(FrameReader { lines })
+    }
+}
+
+impl Iterator for FrameReader {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+
+            // Skip empty lines
+            if !line.trim().is_empty() {
+                return Some(parse_line(&line));
+            }
+        }
+    }
+}
+
+/// Deserialize a single line from a recorded frame file.
+fn parse_line<T: DeserializeOwned>(line: &str) -> Result<T> {
+    serde_json::from_str(line).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use sensor::{FingerFrame, HandFrame};
+    use types::{FingerType, Point3};
+
+    #[test]
+    fn record_and_read() {
+        let path = env::temp_dir().join("cant-touch-this-record-and-read.frames");
+        let frames = vec![
+            Frame::new(0, vec![]),
+            Frame::new(
+                16_000,
+                vec![HandFrame::new(
+                    7,
+                    vec![
+                        FingerFrame::new(FingerType::Thumb, false, Point3::new(1.0, 2.0, 3.0)),
+                        FingerFrame::new(FingerType::Index, true, Point3::new(-4.5, 0.0, 9.25)),
+                    ],
+                )],
+            ),
+        ];
+
+        // Record the frames
+        {
+            let mut recorder = FrameRecorder::create(&path).unwrap();
+            for frame in &frames {
+                recorder.record(frame).unwrap();
+            }
+        }
+
+        // Read them back
+        let read: Vec<Frame> = FrameReader::open(&path)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, frames);
+    }
+}
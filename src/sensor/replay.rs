@@ -0,0 +1,253 @@
+use std::{
+    cmp::min,
+    io,
+    path::PathBuf,
+    str::FromStr,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::{Calibration, FrameReader, SensorListener, SensorSource};
+use config::replay::POLL_INTERVAL;
+
+/// A sensor source replaying frames from a recorded frame file.
+///
+/// Frames are replayed on a separate thread, at the given replay speed. Recorded frames are
+/// calibrated already, so the calibration stored for the replay isn't applied to them.
+pub struct ReplaySource {
+    /// The path of the recorded frame file.
+    path: PathBuf,
+
+    /// The speed to replay frames at.
+    speed: ReplaySpeed,
+
+    /// The replay thread, available once started.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReplaySource {
+    /// Construct a new replay source for the recorded frame file at the given `path`.
+    pub fn new(path: PathBuf, speed: ReplaySpeed) -> Self {
+        Self {
+            path,
+            speed,
+            thread: None,
+        }
+    }
+}
+
+impl SensorSource for ReplaySource {
+    fn name(&self) -> String {
+        format!("replay:{}", self.path.display())
+    }
+
+    fn start(&mut self, mut listener: SensorListener) -> io::Result<()> {
+        // Open the frame file here, to report errors early
+        let reader = FrameReader::open(&self.path)?;
+        let speed = self.speed;
+
+        // Recorded frames are calibrated already, don't calibrate them again
+        listener.set_calibration(Calibration::identity());
+
+        self.thread.replace(thread::spawn(move || {
+            listener.on_connect();
+
+            // The replay start time, and the timestamp of the first frame
+            let start = Instant::now();
+            let mut first_timestamp = None;
+
+            'replay: for frame in reader {
+                if !listener.is_active() {
+                    break;
+                }
+
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        eprintln!("Failed to read frame to replay, stopping replay: {}", err);
+                        break;
+                    }
+                };
+
+                // Wait until it is time for this frame, stop waiting once the sensor is stopped
+                let offset = frame.timestamp - *first_timestamp.get_or_insert(frame.timestamp);
+                if let Some(delay) = speed.delay(offset) {
+                    loop {
+                        let elapsed = start.elapsed();
+                        if delay <= elapsed {
+                            break;
+                        }
+                        if !listener.is_active() {
+                            break 'replay;
+                        }
+                        thread::sleep(min(delay - elapsed, Duration::from_millis(POLL_INTERVAL)));
+                    }
+                }
+
+                listener.on_frame(frame);
+            }
+
+            listener.on_disconnect();
+        }));
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.wait();
+    }
+
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("failed to join replay thread");
+        }
+    }
+}
+
+/// The speed to replay recorded frames at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReplaySpeed {
+    /// Replay frames with their recorded timing, sped up by the given factor.
+    ///
+    /// A factor of `1` replays in real-time.
+    Factor(f64),
+
+    /// Replay frames as fast as possible, ignoring the recorded timing.
+    Unlimited,
+}
+
+impl ReplaySpeed {
+    /// Determine the delay since the start of the replay at which a frame should be replayed,
+    /// based on the given `offset` in microseconds from the first frame timestamp.
+    ///
+    /// `None` is returned if the frame should be replayed immediately.
+    fn delay(&self, offset: i64) -> Option<Duration> {
+        match self {
+            ReplaySpeed::Factor(factor) if offset > 0 => {
+                Some(Duration::from_micros((offset as f64 / factor) as u64))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for ReplaySpeed {
+    fn default() -> ReplaySpeed {
+        ReplaySpeed::Factor(1.0)
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    /// Parse a replay speed, being either a positive speed factor or `max`.
+    fn from_str(speed: &str) -> Result<Self, Self::Err> {
+        if speed == "max" {
+            return Ok(ReplaySpeed::Unlimited);
+        }
+
+        match speed.parse::<f64>() {
+            Ok(factor) if factor > 0.0 => Ok(ReplaySpeed::Factor(factor)),
+            _ => Err(format!(
+                "invalid replay speed '{}', must be a positive factor or 'max'",
+                speed,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::Arc;
+
+    use rand::{prng::XorShiftRng, SeedableRng};
+
+    use super::*;
+    use fragment::FragmentManager;
+    use gesture::{Detection, GestureController};
+    use sensor::{Calibrator, Frame, FrameRecorder, Mount, SensorController, StatusMonitor};
+    use store::{CalibrationStore, TemplateStore};
+    use synth::{Shape, Stroke};
+    use types::{FingerType, HandPart, Point3};
+
+    #[test]
+    fn replay_detects_gestures() {
+        let path = env::temp_dir().join("cant-touch-this-replay-detects-gestures.frames");
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+
+        // Record a circle and a square drawn with the index finger, with a pause in between
+        let strokes = [
+            Stroke::new(Shape::Circle {
+                radius: 55.0,
+                clockwise: true,
+            }),
+            Stroke::new(Shape::Polygon {
+                sides: 4,
+                side: 120.0,
+                clockwise: false,
+            }),
+        ];
+        {
+            let mut recorder = FrameRecorder::create(&path).unwrap();
+            let mut timestamp = 0;
+            for &stroke in &strokes {
+                let stroke = stroke.jitter(0.5).speed(250.0, 0.25);
+                for frame in stroke.frames(1, Point3::new(0.0, 200.0, 0.0), timestamp, &mut rng) {
+                    timestamp = frame.timestamp;
+                    recorder.record(&frame).unwrap();
+                }
+                recorder.record(&Frame::new(timestamp, vec![])).unwrap();
+                timestamp += 1_000_000;
+            }
+        }
+
+        // Replay the frames through the sensor and fragment pipeline, the screen mount must not
+        // rotate the recorded frames as they are calibrated already
+        let gesture_controller = Arc::new(GestureController::new(
+            Arc::new(TemplateStore::builtin()),
+            false,
+            Default::default(),
+            Default::default(),
+        ));
+        let fragment_manager = Arc::new(FragmentManager::new(gesture_controller.clone()));
+        gesture_controller.set_fragment_manager(fragment_manager.clone());
+        let mut controller = SensorController::new(
+            fragment_manager,
+            Arc::new(Calibrator::new(
+                Arc::new(CalibrationStore::new()),
+                Mount::Screen,
+            )),
+            Arc::new(StatusMonitor::new()),
+        );
+        controller
+            .add(
+                Box::new(ReplaySource::new(path.clone(), ReplaySpeed::Unlimited)),
+                None,
+            )
+            .unwrap();
+        controller.wait();
+        fs::remove_file(&path).unwrap();
+
+        // Both strokes are detected, in order
+        let detected: Vec<_> = gesture_controller
+            .flush_detected()
+            .into_iter()
+            .map(|detection| match detection {
+                Detection::Gesture {
+                    template, parts, ..
+                } => (template.name().to_owned(), parts),
+                detection => panic!("unexpected detection: {}", detection),
+            })
+            .collect();
+        let index = vec![(HandPart::Finger(FingerType::Index), None)];
+        assert_eq!(
+            detected,
+            vec![
+                ("Circle clockwise".to_owned(), index.clone()),
+                ("Square counter-clockwise".to_owned(), index),
+            ],
+        );
+    }
+}
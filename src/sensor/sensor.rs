@@ -1,60 +1,162 @@
-use std::sync::Arc;
-
-use leap::{Controller as LeapController, Listener as LeapListener};
+use std::io::Result;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
 
+use super::status::SensorStatus;
+use super::{Calibration, Calibrator, Frame, FrameRecorder, SensorSource};
 use fragment::FragmentManager;
 
+/// The ID of a sensor, unique within a sensor controller.
+pub type SensorId = u32;
+
 /// Structure representing a motion sensor.
 pub struct Sensor {
-    /// The Leap Motion controller instance.
-    _controller: LeapController,
+    /// The source this sensor obtains its frames from.
+    source: Box<dyn SensorSource>,
+
+    /// Whether the sensor is active, shared with the listener.
+    active: Arc<AtomicBool>,
 }
 
 impl Sensor {
-    /// Construct a new sensor with the given listener.
-    pub fn new(listener: SensorListener) -> Self {
-        Self {
-            _controller: LeapController::with_listener(listener),
-        }
+    /// Construct a new sensor on the given source, and start it with the given listener.
+    pub fn start(mut source: Box<dyn SensorSource>, listener: SensorListener) -> Result<Self> {
+        let active = listener.active.clone();
+        source.start(listener)?;
+        Ok(Self { source, active })
+    }
+
+    /// Stop the sensor.
+    ///
+    /// The listener ignores all frames produced from now on, and the source is stopped.
+    pub fn stop(&mut self) {
+        self.active.store(false, Ordering::SeqCst);
+        self.source.stop();
+    }
+
+    /// Block until the sensor source has stopped producing frames.
+    pub fn wait(&mut self) {
+        self.source.wait();
     }
 }
 
 /// A sensor listener.
 ///
-/// This listener handles incomming events from the sensor,
+/// This listener handles incomming events from a sensor source,
 /// and processes it's data.
 pub struct SensorListener {
+    /// The ID of the sensor this listener is for.
+    sensor: SensorId,
+
     /// The global fragment manager.
     fragment_manager: Arc<FragmentManager>,
+
+    /// The sensor calibrator, observing raw frames for the calibration wizard.
+    calibrator: Arc<Calibrator>,
+
+    /// The calibration transform applied to all frames, shared with the calibrator.
+    calibration: Arc<RwLock<Calibration>>,
+
+    /// The sensor status, shared with the status monitor.
+    status: Arc<SensorStatus>,
+
+    /// Whether the sensor is active, frames are ignored if not.
+    active: Arc<AtomicBool>,
+
+    /// An optional recorder, all calibrated frames are written to it if set.
+    recorder: Option<FrameRecorder>,
 }
 
 impl SensorListener {
-    /// Construct a new sensor listener.
-    pub fn new(fragment_manager: Arc<FragmentManager>) -> Self {
-        Self { fragment_manager }
+    /// Construct a new sensor listener for the sensor with the given ID.
+    pub fn new(
+        sensor: SensorId,
+        fragment_manager: Arc<FragmentManager>,
+        calibrator: Arc<Calibrator>,
+        calibration: Arc<RwLock<Calibration>>,
+        status: Arc<SensorStatus>,
+        recorder: Option<FrameRecorder>,
+    ) -> Self {
+        Self {
+            sensor,
+            fragment_manager,
+            calibrator,
+            calibration,
+            status,
+            active: Arc::new(AtomicBool::new(true)),
+            recorder,
+        }
+    }
+
+    /// Check whether the sensor is still active.
+    ///
+    /// Sources producing frames on their own thread should stop once this returns `false`.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Replace the calibration applied to the frames of this sensor, until it is calibrated
+    /// again.
+    ///
+    /// This is used by sources producing frames that are calibrated already, such as replays.
+    pub fn set_calibration(&self, calibration: Calibration) {
+        *self
+            .calibration
+            .write()
+            .expect("failed to lock sensor calibration for writing") = calibration;
     }
-}
 
-impl LeapListener for SensorListener {
-    fn on_frame(&mut self, controller: &LeapController) {
-        // Grab a frame from the controller
-        let frame = controller.frame();
+    /// Handle a new frame produced by the sensor.
+    pub fn on_frame(&mut self, mut frame: Frame) {
+        if !self.is_active() {
+            return;
+        }
+
+        self.status.frame(frame.hands.len());
 
-        // println!(
-        //     "Got update from Leap Motion sensor: {} FPS",
-        //     frame.current_fps(),
-        // );
+        // Let the calibration wizard observe the raw frame
+        self.calibrator.observe(self.sensor, &frame);
+
+        // Transform the frame into the shared coordinate space
+        self.calibration
+            .read()
+            .expect("failed to lock sensor calibration for reading")
+            .apply_frame(&mut frame);
+
+        // Record the calibrated frame, stop recording on failure
+        let failed = match self.recorder {
+            Some(ref mut recorder) => recorder.record(&frame).is_err(),
+            None => false,
+        };
+        if failed {
+            eprintln!("Failed to record sensor frame, stopped recording");
+            self.recorder = None;
+        }
 
         // Process the hand frame data in the hand manager
         self.fragment_manager
-            .process_sensor_hand_list(frame.hands());
+            .process_sensor_hand_list(self.sensor, frame.timestamp, &frame.hands);
+    }
+
+    /// Handle frames produced by the sensor that were dropped, such as invalid or skipped frames.
+    pub fn on_frames_dropped(&mut self, count: u64) {
+        self.status.dropped(count);
     }
 
-    fn on_connect(&mut self, _: &LeapController) {
-        println!("Leap Motion sensor connected");
+    /// Handle the sensor being connected.
+    pub fn on_connect(&mut self) {
+        println!("Sensor {} connected", self.sensor);
+        self.status.set_connected(true);
     }
 
-    fn on_disconnect(&mut self, _: &LeapController) {
-        println!("Leap Motion sensor disconnected");
+    /// Handle the sensor being disconnected.
+    ///
+    /// The hands tracked by the sensor are stale now, and are dropped.
+    pub fn on_disconnect(&mut self) {
+        println!("Sensor {} disconnected", self.sensor);
+        self.status.set_connected(false);
+        self.fragment_manager.remove_sensor_hands(self.sensor);
     }
 }
@@ -0,0 +1,35 @@
+use std::io::Result;
+
+use super::SensorListener;
+
+/// A source of sensor frames.
+///
+/// A source produces hand and finger frames from some kind of input, such as a Leap Motion
+/// device, and pushes them into the given listener for processing.
+///
+/// Sources are `Send`, so sensors may be added and removed at runtime from any thread.
+pub trait SensorSource: Send {
+    /// A name identifying this source.
+    ///
+    /// This should be the same each run for the same source, as it's used to restore the sensor
+    /// calibration.
+    fn name(&self) -> String;
+
+    /// Start the source.
+    ///
+    /// Frames and connection events produced from now on must be passed to the given `listener`.
+    fn start(&mut self, listener: SensorListener) -> Result<()>;
+
+    /// Stop the source.
+    ///
+    /// This is called after the listener stopped being active. Sources producing frames on their
+    /// own thread may instead stop once the listener reports it isn't active anymore. Sources
+    /// holding resources such as sockets on their thread should wait here for it to end, so the
+    /// resources are released once the source is stopped.
+    fn stop(&mut self) {}
+
+    /// Block until the source has stopped producing frames.
+    ///
+    /// Sources that keep producing frames indefinitely return immediately.
+    fn wait(&mut self) {}
+}
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::SensorId;
+
+/// The time window in milliseconds over which the frame rate is measured.
+const FRAME_RATE_WINDOW: u64 = 1000;
+
+/// The sensor status monitor.
+///
+/// This keeps track of the status of each running sensor, for health and telemetry reporting.
+///
+/// The monitor may be shared and used from any context, such as the web server.
+pub struct StatusMonitor {
+    /// The status of running sensors, by their ID.
+    sensors: Mutex<BTreeMap<SensorId, Arc<SensorStatus>>>,
+}
+
+impl StatusMonitor {
+    /// Construct a new status monitor.
+    pub fn new() -> Self {
+        Self {
+            sensors: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Register a sensor with the given ID and name.
+    ///
+    /// The returned status should be updated by the sensor listener.
+    pub fn register(&self, id: SensorId, name: String) -> Arc<SensorStatus> {
+        let status = Arc::new(SensorStatus::new(name));
+        self.sensors
+            .lock()
+            .expect("failed to lock monitored sensors to register sensor")
+            .insert(id, status.clone());
+        status
+    }
+
+    /// Unregister the sensor with the given ID.
+    pub fn unregister(&self, id: SensorId) {
+        self.sensors
+            .lock()
+            .expect("failed to lock monitored sensors to unregister sensor")
+            .remove(&id);
+    }
+
+    /// Report the status of all registered sensors.
+    pub fn reports(&self) -> Vec<StatusReport> {
+        self.sensors
+            .lock()
+            .expect("failed to lock monitored sensors to report status")
+            .iter()
+            .map(|(&id, status)| status.report(id))
+            .collect()
+    }
+}
+
+/// The status of a single sensor.
+pub struct SensorStatus {
+    /// The sensor name.
+    name: String,
+
+    /// The mutable status state.
+    state: Mutex<StatusState>,
+}
+
+impl SensorStatus {
+    /// Construct a new status for a disconnected sensor with the given `name`.
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            state: Mutex::new(StatusState::default()),
+        }
+    }
+
+    /// Set whether the sensor is connected.
+    pub fn set_connected(&self, connected: bool) {
+        self.state().connected = connected;
+    }
+
+    /// Track a frame received from the sensor, with the given number of hands in view.
+    pub fn frame(&self, hands: usize) {
+        let now = Instant::now();
+        let mut state = self.state();
+        state.frames += 1;
+        state.hands = hands;
+        state.last_frame = Some(now);
+        state.recent.push_back(now);
+        state.trim_recent(now);
+    }
+
+    /// Track the given number of frames that were dropped.
+    pub fn dropped(&self, count: u64) {
+        self.state().dropped += count;
+    }
+
+    /// Report the current status, for the sensor with the given ID.
+    pub fn report(&self, id: SensorId) -> StatusReport {
+        let now = Instant::now();
+        let mut state = self.state();
+        state.trim_recent(now);
+
+        StatusReport {
+            id,
+            name: self.name.clone(),
+            connected: state.connected,
+            frame_rate: state.recent.len() as f64 * 1000.0 / FRAME_RATE_WINDOW as f64,
+            frames: state.frames,
+            frames_dropped: state.dropped,
+            hands: state.hands,
+            last_frame_age: state.last_frame.map(|last| {
+                let age = now - last;
+                age.as_secs() * 1000 + u64::from(age.subsec_millis())
+            }),
+        }
+    }
+
+    /// Lock the status state.
+    fn state(&self) -> MutexGuard<StatusState> {
+        self.state
+            .lock()
+            .expect("failed to lock sensor status state")
+    }
+}
+
+/// The mutable state of a sensor status.
+#[derive(Default)]
+struct StatusState {
+    /// Whether the sensor is connected.
+    connected: bool,
+
+    /// The total number of frames received.
+    frames: u64,
+
+    /// The total number of frames dropped.
+    dropped: u64,
+
+    /// The number of hands in view in the last frame.
+    hands: usize,
+
+    /// The time the last frame was received at.
+    last_frame: Option<Instant>,
+
+    /// The times frames were received at within the frame rate window.
+    recent: VecDeque<Instant>,
+}
+
+impl StatusState {
+    /// Forget frames received before the frame rate window, relative to `now`.
+    fn trim_recent(&mut self, now: Instant) {
+        let window = Duration::from_millis(FRAME_RATE_WINDOW);
+        while self
+            .recent
+            .front()
+            .map_or(false, |&time| now - time > window)
+        {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// A status report of a sensor.
+#[derive(Serialize)]
+pub struct StatusReport {
+    /// The sensor ID.
+    pub id: SensorId,
+
+    /// The sensor name.
+    pub name: String,
+
+    /// Whether the sensor is connected.
+    pub connected: bool,
+
+    /// The number of frames received per second.
+    pub frame_rate: f64,
+
+    /// The total number of frames received.
+    pub frames: u64,
+
+    /// The total number of frames dropped.
+    pub frames_dropped: u64,
+
+    /// The number of hands in view in the last frame.
+    pub hands: usize,
+
+    /// The time in milliseconds since the last frame was received, if any.
+    pub last_frame_age: Option<u64>,
+}
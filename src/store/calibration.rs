@@ -0,0 +1,126 @@
+use std::{collections::HashMap, fs, io::Result, path::PathBuf, sync::Mutex};
+
+use directories::ProjectDirs;
+use serde_json;
+
+use config::calibration::CALIBRATIONS_FILE;
+use sensor::Calibration;
+
+/// Used for storing sensor calibrations.
+///
+/// Calibrations are stored by sensor name, so they are restored for the same sensor on the next
+/// run.
+#[derive(Debug)]
+pub struct CalibrationStore {
+    calibrations: Mutex<HashMap<String, Calibration>>,
+}
+
+impl CalibrationStore {
+    /// Construct a new empty calibration store.
+    pub fn new() -> Self {
+        Self {
+            calibrations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the calibration for the sensor with the given `name`.
+    ///
+    /// If the sensor isn't calibrated, `None` is returned instead.
+    pub fn get(&self, name: &str) -> Option<Calibration> {
+        self.calibrations
+            .lock()
+            .expect("failed to lock calibrations to get item")
+            .get(name)
+            .cloned()
+    }
+
+    /// Set the calibration for the sensor with the given `name`.
+    pub fn set(&self, name: String, calibration: Calibration) -> Result<()> {
+        self.calibrations
+            .lock()
+            .expect("failed to lock calibrations to set item")
+            .insert(name, calibration);
+
+        // Save the results
+        self.save()
+    }
+
+    /// Delete the calibration for the sensor with the given `name`.
+    pub fn delete(&self, name: &str) -> Result<()> {
+        self.calibrations
+            .lock()
+            .expect("failed to lock calibrations to delete item")
+            .remove(name);
+
+        // Save the results
+        self.save()
+    }
+
+    /// Load the calibrations from a file.
+    /// On success, the current calibrations are replaced with the calibrations from the file.
+    ///
+    /// If the file doesn't exist, nothing is loaded and `Ok` is returned.
+    pub fn load(&self) -> Result<()> {
+        // Get the file path
+        let file = Self::file();
+
+        // Ensure a file exists
+        if !file.is_file() {
+            return Ok(());
+        }
+
+        // Obtain a calibrations lock
+        let mut calibrations = self
+            .calibrations
+            .lock()
+            .expect("failed to lock calibrations for loading");
+
+        // Load, deserialize and set the calibrations
+        *calibrations = serde_json::from_str(&fs::read_to_string(file)?)
+            .expect("failed to deserialize calibrations from loaded file");
+
+        println!("Loaded {} sensor calibration(s)", calibrations.len());
+
+        Ok(())
+    }
+
+    /// Save the current calibrations to a file.
+    pub fn save(&self) -> Result<()> {
+        // Obtain a calibrations lock
+        let calibrations = self
+            .calibrations
+            .lock()
+            .expect("failed to lock calibrations for saving");
+
+        // Determine where to save
+        let file = Self::file();
+
+        // Remove the calibrations file if there are no calibrations to save
+        if calibrations.is_empty() {
+            let _ = fs::remove_file(file);
+            return Ok(());
+        }
+
+        // Create all parent directories
+        fs::create_dir_all(
+            file.parent()
+                .expect("failed to determine parent directory of calibrations file location"),
+        )
+        .expect("failed to create directory to store calibrations file in");
+
+        // Write the file
+        fs::write(
+            file,
+            serde_json::to_string_pretty(&*calibrations)
+                .expect("failed to serialize calibration data, unable to save"),
+        )
+    }
+
+    /// Get the file the calibrations are saved to.
+    fn file() -> PathBuf {
+        ProjectDirs::from("", "", crate_name!())
+            .unwrap()
+            .config_dir()
+            .join(CALIBRATIONS_FILE)
+    }
+}
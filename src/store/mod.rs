@@ -1,6 +1,8 @@
 //! Template store module
 
+pub mod calibration;
 pub mod template;
 
 /// Re-export
+pub use self::calibration::CalibrationStore;
 pub use self::template::TemplateStore;
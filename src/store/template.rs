@@ -1,17 +1,37 @@
-use std::{fs, io::Result, path::PathBuf, sync::Mutex};
+use std::{
+    cmp::{Ordering as CmpOrdering, Reverse},
+    fs,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use directories::ProjectDirs;
 use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json;
 
-use config::{sample::DISTANCE, template::TEMPLATES_FILE};
+use config::{
+    sample::DISTANCE,
+    template::{POSTURES_FILE, TEMPLATES_FILE},
+};
 use fragment::Fragment;
-use types::{Model, RotPoint, RotTrace, Template};
+use types::{
+    FingerType, HandPose, HandSide, MatchScore, Model, ModelTrace, PlaneMode, PoseConstraints,
+    PostureTemplate, RotPoint, RotTrace, Template,
+};
 
-/// Used for storing templates.
+/// Used for storing templates, and posture templates.
 #[derive(Debug)]
 pub struct TemplateStore {
     templates: Mutex<Vec<Template>>,
+    postures: Mutex<Vec<PostureTemplate>>,
+
+    /// Whether any template matches in a fitted drawing plane, updated when templates change.
+    fitted: AtomicBool,
 }
 
 impl TemplateStore {
@@ -19,11 +39,58 @@ impl TemplateStore {
     pub fn new() -> Self {
         Self {
             templates: Mutex::new(Vec::new()),
+            postures: Mutex::new(Vec::new()),
+            fitted: AtomicBool::new(false),
         }
     }
 
+    /// Construct a new template store holding the given templates and posture templates, without
+    /// loading or saving them.
+    #[cfg(test)]
+    pub fn with_templates(templates: Vec<Template>, postures: Vec<PostureTemplate>) -> Self {
+        let store = Self::new();
+        *store
+            .templates
+            .lock()
+            .expect("failed to lock templates list") = templates;
+        *store.postures.lock().expect("failed to lock postures list") = postures;
+        store.update_fitted();
+        store
+    }
+
+    /// Construct a new template store holding the built-in templates and posture templates,
+    /// without loading or saving them.
+    #[cfg(test)]
+    pub fn builtin() -> Self {
+        Self::with_templates(builtin_templates(), builtin_postures())
+    }
+
+    /// Check whether any template matches traces in a fitted drawing plane.
+    ///
+    /// This is cheap, so it can be checked for each sensed point.
+    #[inline]
+    pub fn uses_fitted_plane(&self) -> bool {
+        self.fitted.load(Ordering::Relaxed)
+    }
+
+    /// Update whether any template matches traces in a fitted drawing plane, after the list of
+    /// templates changed.
+    fn update_fitted(&self) {
+        let fitted = self
+            .templates
+            .lock()
+            .expect("failed to lock templates list")
+            .iter()
+            .any(|template| template.plane() == PlaneMode::Fitted);
+        self.fitted.store(fitted, Ordering::Relaxed);
+    }
+
     /// Add the given template.
+    ///
+    /// Templates with an empty model are rejected.
     pub fn add(&self, template: Template) -> Result<()> {
+        check_model(&template)?;
+
         // Add the template
         self.templates
             .lock()
@@ -35,7 +102,13 @@ impl TemplateStore {
     }
 
     /// Add a list of templates.
+    ///
+    /// If any template has an empty model, none of the templates are added.
     pub fn add_list(&self, mut templates: Vec<Template>) -> Result<()> {
+        for template in &templates {
+            check_model(template)?;
+        }
+
         // Add the template
         self.templates
             .lock()
@@ -46,12 +119,28 @@ impl TemplateStore {
         self.save()
     }
 
-    /// Add the built-in list of templates to the store.
+    /// Add the given posture template.
+    pub fn add_posture(&self, posture: PostureTemplate) -> Result<()> {
+        // Add the posture
+        self.postures
+            .lock()
+            .expect("failed to lock postures list to add item")
+            .push(posture);
+
+        // Save the results
+        self.save()
+    }
+
+    /// Add the built-in list of templates and posture templates to the store.
     pub fn add_builtin(&self) -> Result<()> {
+        self.postures
+            .lock()
+            .expect("failed to lock postures list to add items")
+            .append(&mut builtin_postures());
         self.add_list(builtin_templates())
     }
 
-    /// Delete the template with the given `id`.
+    /// Delete the template or posture template with the given `id`.
     /// Nothing happends if no template exists with the specified `id`.
     ///
     /// This method is expensive, as it clones the list of templates.
@@ -61,18 +150,26 @@ impl TemplateStore {
             .lock()
             .expect("failed to lock templates list to remove item")
             .retain(|template| template.id() != id);
+        self.postures
+            .lock()
+            .expect("failed to lock postures list to remove item")
+            .retain(|posture| posture.id() != id);
 
         // Save the results
         self.save()
     }
 
-    /// Delete all templates.
+    /// Delete all templates and posture templates.
     pub fn delete_all(&self) -> Result<()> {
         // Remove the template
         self.templates
             .lock()
             .expect("failed to lock templates list to delete all")
             .clear();
+        self.postures
+            .lock()
+            .expect("failed to lock postures list to delete all")
+            .clear();
 
         // Save the results
         self.save()
@@ -88,87 +185,91 @@ impl TemplateStore {
             .clone()
     }
 
-    /// Load a list of templates from a file.
-    /// On success, the current list of templates is replaced with the list of tempaltes from the
-    /// file.
+    /// Get a list of posture templates available in this store.
     ///
-    /// If the file doesn't exist, nothing is loaded and `Ok` is returned.
-    pub fn load(&self) -> Result<()> {
-        // Get the file path
-        let file = Self::file();
-
-        // Ensure a file exists
-        if !file.is_file() {
-            eprintln!("Not loading templates, no file exists");
-            return Ok(());
-        }
-
-        // Obtain a templates list lock
-        let mut templates = self
-            .templates
+    /// This method is expensive, as it clones the list of posture templates.
+    pub fn to_postures(&self) -> Vec<PostureTemplate> {
+        self.postures
             .lock()
-            .expect("failed to lock templates list for loading");
-
-        println!("Loading templates from {}...", file.to_str().unwrap_or("?"));
-
-        // Load, deserialize and set the list of templates
-        *templates = serde_json::from_str(&fs::read_to_string(file)?)
-            .expect("failed to deserialize templates from loaded file");
+            .expect("failed to lock postures list")
+            .clone()
+    }
 
-        println!("Loaded {} template(s)", templates.len());
+    /// Load the lists of templates and posture templates from their files.
+    /// On success, the current lists are replaced with the lists from the files.
+    ///
+    /// If a file doesn't exist, its list isn't loaded. Templates with an empty model are skipped.
+    pub fn load(&self) -> Result<()> {
+        if let Some(mut templates) = load_list(&Self::file(TEMPLATES_FILE), "template")? {
+            templates.retain(|template: &Template| match check_model(template) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("Not loading template: {}", err);
+                    false
+                }
+            });
+            *self
+                .templates
+                .lock()
+                .expect("failed to lock templates list for loading") = templates;
+            self.update_fitted();
+        }
+        if let Some(postures) = load_list(&Self::file(POSTURES_FILE), "posture")? {
+            *self
+                .postures
+                .lock()
+                .expect("failed to lock postures list for loading") = postures;
+        }
 
         Ok(())
     }
 
-    /// Save the current list of templates to a file.
+    /// Save the current lists of templates and posture templates to their files.
     ///
-    /// TODO: handle errors properly, return an error on failure instead of panicing.
+    /// This is done after each change, so the templates are inspected for changes here as well.
     pub fn save(&self) -> Result<()> {
-        // Obtain a templates list lock
-        let templates = self
-            .templates
-            .lock()
-            .expect("failed to lock templates list for saving");
-
-        // Determine where to save
-        let file = Self::file();
-
-        // Remove template files if there are not tempaltes to save
-        if templates.is_empty() {
-            let _ = fs::remove_file(file);
-            return Ok(());
-        }
-
-        println!(
-            "Saving {} template(s) to {}...",
-            templates.len(),
-            file.to_str().unwrap_or("?"),
-        );
+        self.update_fitted();
 
-        // Create all parent directories
-        fs::create_dir_all(
-            file.parent()
-                .expect("failed to determine parent directory of template save file location"),
-        )
-        .expect("failed to create directory to store templates file in");
-
-        // Write the file
-        fs::write(
-            file,
-            serde_json::to_string_pretty(&*templates)
-                .expect("failed to serialize template data, unable to save"),
+        save_list(
+            &Self::file(TEMPLATES_FILE),
+            &self
+                .templates
+                .lock()
+                .expect("failed to lock templates list for saving"),
+            "template",
+        )?;
+        save_list(
+            &Self::file(POSTURES_FILE),
+            &self
+                .postures
+                .lock()
+                .expect("failed to lock postures list for saving"),
+            "posture",
         )
     }
 
-    /// Find a matching template in this template store, for the given `other` fragment.
+    /// Find a matching template in this template store, for the given `other` fragment of a hand
+    /// in the given `pose`.
     /// This may be used for gesture detection based on templates.
     ///
-    /// This attempts to find a matching template in parallel. Only one match may be returned, and
-    /// searching for a match is stalled when a first match is found.
+    /// Only templates applying to the hand part traced by the fragment, and allowing the hand
+    /// pose are considered. Templates recorded with the other hand are matched mirrored if they
+    /// allow so. Templates with a multi-trace model are matched through `find_partial_matches`
+    /// instead, templates with an empty model are skipped.
+    ///
+    /// If the fragment has a completed stroke in segmentation mode, the whole stroke is matched
+    /// instead of the end of its trace.
+    ///
+    /// This scores templates in parallel, and returns the best matching template along with its
+    /// score, being the one with the smallest distance.
     ///
     /// If no template is matching, `None` is returned.
     #[inline]
-    pub fn find_matching(&self, other: &mut Fragment) -> Option<Template> {
+    pub fn find_matching(
+        &self,
+        other: &Fragment,
+        pose: &HandPose,
+    ) -> Option<(Template, MatchScore)> {
         // Obtain a templates list lock, and attempt to find a matching template
         // TODO: do not clone to improve performance
         let templates = self
@@ -177,25 +278,294 @@ impl TemplateStore {
             .expect("failed to lock templates list for detecting gestures");
 
         // Match if there is any
+        let part = other.part();
         if !templates.is_empty() {
+            let candidate = Candidate::new(other);
             templates
                 .par_iter()
-                .find_any(|template| template.model().matches(other.model()))
-                .cloned()
+                .filter(|template| !template.model().is_multi() && !template.model().is_empty())
+                .filter(|template| template.applies_to(part) && template.allows_pose(pose))
+                .filter_map(|template| {
+                    let trace = template.model().traces().first()?;
+                    candidate
+                        .score(template, trace)
+                        .map(|(score, _)| (template, score))
+                })
+                .min_by(|(_, a), (_, b)| {
+                    a.distance()
+                        .partial_cmp(&b.distance())
+                        .unwrap_or(CmpOrdering::Equal)
+                })
+                .map(|(template, score)| (template.clone(), score))
         } else {
             None
         }
     }
 
-    /// Get the file the templates are saved to.
-    fn file<'a>() -> PathBuf {
+    /// Find the traces of templates with a multi-trace model that match the given fragment
+    /// `other`, of a hand in the given `pose`.
+    ///
+    /// Each matching template is returned along with the index of the matching trace in its
+    /// model, whether it matched mirrored and its score. A template is only detected once all its
+    /// traces have matched, which is decided by the caller.
+    pub fn find_partial_matches(
+        &self,
+        other: &Fragment,
+        pose: &HandPose,
+    ) -> Vec<(Template, usize, bool, MatchScore)> {
+        let part = other.part();
+        let candidate = Candidate::new(other);
+        self.templates
+            .lock()
+            .expect("failed to lock templates list for detecting gestures")
+            .iter()
+            .filter(|template| template.model().is_multi() && !template.model().is_empty())
+            .filter(|template| template.applies_to(part) && template.allows_pose(pose))
+            .flat_map(|template| {
+                template
+                    .model()
+                    .traces()
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, trace)| {
+                        candidate
+                            .score(template, trace)
+                            .map(|(score, mirrored)| (template.clone(), i, mirrored, score))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Find the most specific posture template matching the given hand `pose`, see
+    /// `PostureTemplate::specificity`. Of equally specific postures, the first is picked.
+    ///
+    /// If no posture template is matching, `None` is returned.
+    pub fn find_posture(&self, pose: &HandPose) -> Option<PostureTemplate> {
+        self.postures
+            .lock()
+            .expect("failed to lock postures list for detecting postures")
+            .iter()
+            .filter(|posture| posture.matches(pose))
+            .min_by_key(|posture| Reverse(posture.specificity()))
+            .cloned()
+    }
+
+    /// Get the file with the given `name` templates are saved to.
+    fn file(name: &str) -> PathBuf {
         ProjectDirs::from("", "", crate_name!())
             .unwrap()
             .cache_dir()
-            .join(TEMPLATES_FILE)
+            .join(name)
+    }
+}
+
+/// A fragment trace to match against templates.
+struct Candidate<'a> {
+    /// The trace projected onto the XY plane.
+    fixed: CandidateTrace<'a>,
+
+    /// The trace projected onto the drawing plane fitted through recent points.
+    fitted: CandidateTrace<'a>,
+
+    /// Whether the trace is a whole stroke, that must be matched as a whole.
+    whole: bool,
+
+    /// The side of the hand, if known.
+    side: Option<HandSide>,
+}
+
+impl<'a> Candidate<'a> {
+    /// Construct a new candidate for the given fragment.
+    fn new(fragment: &'a Fragment) -> Self {
+        let trace = |mode| {
+            let trace = fragment
+                .segment(mode)
+                .unwrap_or_else(|| fragment.trace_in(mode));
+            CandidateTrace {
+                trace,
+                mirrored: Mutex::new(None),
+            }
+        };
+        Self {
+            fixed: trace(PlaneMode::Fixed),
+            fitted: trace(PlaneMode::Fitted),
+            whole: fragment.segment(PlaneMode::Fixed).is_some(),
+            side: fragment.side(),
+        }
+    }
+
+    /// Match the given model `trace` of the `template` against this candidate.
+    ///
+    /// The candidate trace in the drawing plane of the template is used, mirrored if the template
+    /// applies mirrored to the hand. Spatial templates match the pitch of points as well, scale
+    /// invariant templates match the candidate at any size.
+    /// Returns the score and whether the trace matched mirrored, or `None` if it didn't match.
+    /// Scores with a distance that isn't finite, such as for a trace with invalid points, never
+    /// match.
+    #[inline]
+    fn score(&self, template: &Template, trace: &ModelTrace) -> Option<(MatchScore, bool)> {
+        let candidate = match template.plane() {
+            PlaneMode::Fixed => &self.fixed,
+            PlaneMode::Fitted => &self.fitted,
+        };
+        let mirror = template.mirrored(trace, self.side)?;
+        let mirrored;
+        let other = if mirror {
+            mirrored = candidate.mirrored();
+            &*mirrored
+        } else {
+            candidate.trace
+        };
+        let (model, spatial) = (trace.trace(), template.spatial());
+        let score = if template.scale_invariant() {
+            model.score_scaled(other, self.whole, spatial)
+        } else if self.whole {
+            model.score_whole(other, spatial)
+        } else {
+            Some(model.score(other, spatial))
+        };
+        score
+            .filter(|score| score.is_match() && score.distance().is_finite())
+            .map(|score| (score, mirror))
+    }
+}
+
+/// A fragment trace in a single drawing plane, to match against templates.
+struct CandidateTrace<'a> {
+    /// The trace to match, being the completed stroke in segmentation mode.
+    trace: &'a RotTrace,
+
+    /// The mirrored trace, once a template matched it mirrored.
+    mirrored: Mutex<Option<Arc<RotTrace>>>,
+}
+
+impl<'a> CandidateTrace<'a> {
+    /// Get the mirrored trace.
+    ///
+    /// It is only mirrored the first time it is needed, as most templates don't match mirrored
+    /// traces.
+    fn mirrored(&self) -> Arc<RotTrace> {
+        let trace = self.trace;
+        self.mirrored
+            .lock()
+            .expect("failed to lock mirrored candidate trace")
+            .get_or_insert_with(|| Arc::new(trace.mirrored()))
+            .clone()
     }
 }
 
+/// Make sure the given `template` has a model that isn't empty, as an empty model can't be
+/// matched.
+fn check_model(template: &Template) -> Result<()> {
+    if template.model().is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("template '{}' has an empty model", template.name()),
+        ));
+    }
+    Ok(())
+}
+
+/// Load a list of items from the given `file`, naming the items `kind` in messages.
+///
+/// If the file doesn't exist, nothing is loaded and `None` is returned.
+fn load_list<T: DeserializeOwned>(file: &Path, kind: &str) -> Result<Option<Vec<T>>> {
+    // Ensure a file exists
+    if !file.is_file() {
+        eprintln!("Not loading {}s, no file exists", kind);
+        return Ok(None);
+    }
+
+    println!("Loading {}s from {}...", kind, file.to_str().unwrap_or("?"));
+
+    // Load and deserialize the list
+    let list: Vec<T> = serde_json::from_str(&fs::read_to_string(file)?)
+        .expect("failed to deserialize templates from loaded file");
+
+    println!("Loaded {} {}(s)", list.len(), kind);
+
+    Ok(Some(list))
+}
+
+/// Save the given list of items to the given `file`, naming the items `kind` in messages.
+///
+/// TODO: handle errors properly, return an error on failure instead of panicing.
+fn save_list<T: Serialize>(file: &Path, list: &[T], kind: &str) -> Result<()> {
+    // Remove the file if there are no items to save
+    if list.is_empty() {
+        let _ = fs::remove_file(file);
+        return Ok(());
+    }
+
+    println!(
+        "Saving {} {}(s) to {}...",
+        list.len(),
+        kind,
+        file.to_str().unwrap_or("?"),
+    );
+
+    // Create all parent directories
+    fs::create_dir_all(
+        file.parent()
+            .expect("failed to determine parent directory of template save file location"),
+    )
+    .expect("failed to create directory to store templates file in");
+
+    // Write the file
+    fs::write(
+        file,
+        serde_json::to_string_pretty(list)
+            .expect("failed to serialize template data, unable to save"),
+    )
+}
+
+/// Generate a list of built-in posture templates.
+fn builtin_postures() -> Vec<PostureTemplate> {
+    vec![
+        PostureTemplate::new(
+            "Open hand".into(),
+            Some(vec![
+                FingerType::Thumb,
+                FingerType::Index,
+                FingerType::Middle,
+                FingerType::Ring,
+                FingerType::Pinky,
+            ]),
+            PoseConstraints::default(),
+        ),
+        PostureTemplate::new(
+            "Fist".into(),
+            Some(vec![]),
+            PoseConstraints {
+                grab: true,
+                ..PoseConstraints::default()
+            },
+        ),
+        PostureTemplate::new(
+            "Thumbs up".into(),
+            Some(vec![FingerType::Thumb]),
+            PoseConstraints::default(),
+        ),
+        PostureTemplate::new(
+            "Two fingers".into(),
+            None,
+            PoseConstraints {
+                extended: Some(2),
+                ..PoseConstraints::default()
+            },
+        ),
+        PostureTemplate::new(
+            "Three fingers".into(),
+            None,
+            PoseConstraints {
+                extended: Some(3),
+                ..PoseConstraints::default()
+            },
+        ),
+    ]
+}
+
 /// Generate a list of built-in templates.
 fn builtin_templates() -> Vec<Template> {
     vec![
@@ -493,3 +863,152 @@ fn builtin_templates() -> Vec<Template> {
         ),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{prng::XorShiftRng, Rng, SeedableRng};
+
+    use super::*;
+    use synth::{Shape, Stroke};
+    use types::{Point3, PointTrace};
+
+    /// Get a stroke for drawing the shape of the built-in template with the given `name`.
+    ///
+    /// Polygons are drawn a little smaller than their templates. The search position of the
+    /// matcher lags behind on straight sides, which would put the corners of larger polygons
+    /// outside its search space.
+    fn builtin_stroke(name: &str) -> Stroke {
+        let shape = match name {
+            "Long straight line" => Shape::Line { length: 450.0 },
+            "Circle clockwise" => Shape::Circle {
+                radius: 55.0,
+                clockwise: true,
+            },
+            "Circle counter-clockwise" => Shape::Circle {
+                radius: 55.0,
+                clockwise: false,
+            },
+            "Big circle clockwise" => Shape::Circle {
+                radius: 145.0,
+                clockwise: true,
+            },
+            "Big circle counter-clockwise" => Shape::Circle {
+                radius: 145.0,
+                clockwise: false,
+            },
+            "Triangle clockwise" => Shape::Polygon {
+                sides: 3,
+                side: 120.0,
+                clockwise: true,
+            },
+            "Triangle counter-clockwise" => Shape::Polygon {
+                sides: 3,
+                side: 120.0,
+                clockwise: false,
+            },
+            "Mini square clockwise" => Shape::Polygon {
+                sides: 4,
+                side: 70.0,
+                clockwise: true,
+            },
+            "Square clockwise" => Shape::Polygon {
+                sides: 4,
+                side: 120.0,
+                clockwise: true,
+            },
+            "Square counter-clockwise" => Shape::Polygon {
+                sides: 4,
+                side: 120.0,
+                clockwise: false,
+            },
+            name => panic!("no stroke defined for built-in template '{}'", name),
+        };
+        Stroke::new(shape)
+    }
+
+    #[test]
+    fn builtin_detected() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+
+        for template in builtin_templates() {
+            // Draw each shape a few times, with random rotation and noise
+            for _ in 0..10 {
+                let stroke = builtin_stroke(template.name())
+                    .rotation(rng.gen_range(0.0, 2.0 * PI))
+                    .jitter(0.5)
+                    .speed(250.0, 0.25);
+                let trace = PointTrace::new(stroke.points(Point3::zero(), &mut rng));
+                let trace = Model::new(trace.to_rot_trace(true));
+
+                assert!(
+                    template.model().matches(&trace, false),
+                    "built-in template '{}' not detected in drawn stroke",
+                    template.name(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_rejected() {
+        let mut rng = XorShiftRng::from_seed([7; 16]);
+        let templates = builtin_templates();
+        let template = |name: &str| {
+            templates
+                .iter()
+                .find(|template| template.name() == name)
+                .expect("failed to find built-in template")
+        };
+
+        // Templates must not be detected in strokes of other shapes, or drawn the other way around
+        let rejected = [
+            ("Circle clockwise", "Square clockwise"),
+            ("Circle clockwise", "Mini square clockwise"),
+            ("Circle clockwise", "Long straight line"),
+            ("Circle clockwise", "Circle counter-clockwise"),
+            ("Circle counter-clockwise", "Circle clockwise"),
+            ("Circle counter-clockwise", "Square counter-clockwise"),
+            ("Big circle clockwise", "Big circle counter-clockwise"),
+            ("Big circle counter-clockwise", "Big circle clockwise"),
+            ("Triangle clockwise", "Triangle counter-clockwise"),
+            ("Triangle counter-clockwise", "Triangle clockwise"),
+            ("Square clockwise", "Square counter-clockwise"),
+            ("Square counter-clockwise", "Square clockwise"),
+            ("Mini square clockwise", "Square counter-clockwise"),
+            ("Long straight line", "Circle clockwise"),
+        ];
+        for &(name, drawn) in &rejected {
+            for _ in 0..10 {
+                let stroke = builtin_stroke(drawn)
+                    .rotation(rng.gen_range(0.0, 2.0 * PI))
+                    .jitter(0.5)
+                    .speed(250.0, 0.25);
+                let trace = PointTrace::new(stroke.points(Point3::zero(), &mut rng));
+                let trace = Model::new(trace.to_rot_trace(true));
+
+                assert!(
+                    !template(name).model().matches(&trace, false),
+                    "built-in template '{}' detected in '{}' stroke",
+                    name,
+                    drawn,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn empty_model_rejected() {
+        // Empty models are rejected before saving, so nothing is written
+        let store = TemplateStore::with_templates(vec![], vec![]);
+        let empty = Template::new("Empty".into(), Model::from_traces(vec![]));
+        let no_points = Template::new("No points".into(), Model::new(RotTrace::empty()));
+        assert!(store.add(empty.clone()).is_err());
+        assert!(store.add(no_points).is_err());
+        assert!(store
+            .add_list(vec![builtin_templates().remove(0), empty])
+            .is_err());
+        assert!(store.to_templates().is_empty());
+    }
+}
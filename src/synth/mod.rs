@@ -0,0 +1,11 @@
+//! Synthetic gesture generation.
+//!
+//! This produces realistic finger trajectories for parametric shapes, to drive the recognition
+//! pipeline in tests and demos without a physical sensor.
+
+pub mod shape;
+pub mod stroke;
+
+/// Re-exports
+pub use self::shape::Shape;
+pub use self::stroke::Stroke;
@@ -0,0 +1,90 @@
+use std::f64::consts::PI;
+
+/// The number of outline points to generate for each full turn of a curved shape.
+const CURVE_RESOLUTION: usize = 360;
+
+/// A parametric shape that may be drawn as a stroke.
+///
+/// Shapes are defined in a 2D plane, with sizes in millimeters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A straight line of the given length.
+    Line { length: f64 },
+
+    /// A full circle with the given radius.
+    Circle { radius: f64, clockwise: bool },
+
+    /// A closed regular polygon with the given number of sides and side length.
+    Polygon {
+        sides: usize,
+        side: f64,
+        clockwise: bool,
+    },
+
+    /// An Archimedean spiral, starting in the center, with the given outer radius and number of
+    /// turns.
+    Spiral {
+        radius: f64,
+        turns: f64,
+        clockwise: bool,
+    },
+}
+
+impl Shape {
+    /// Build the outline of this shape, as a list of 2D points to draw along in order.
+    ///
+    /// Straight parts are defined by their corner points only, curved parts are approximated.
+    pub fn outline(&self) -> Vec<(f64, f64)> {
+        match *self {
+            Shape::Line { length } => vec![(0.0, 0.0), (length, 0.0)],
+
+            Shape::Circle { radius, clockwise } => (0..=CURVE_RESOLUTION)
+                .map(|i| i as f64 / CURVE_RESOLUTION as f64)
+                .map(|t| {
+                    let angle = direction(clockwise) * 2.0 * PI * t;
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect(),
+
+            Shape::Polygon {
+                sides,
+                side,
+                clockwise,
+            } => {
+                let turn = direction(clockwise) * 2.0 * PI / sides as f64;
+                (0..=sides)
+                    .scan((0.0, 0.0), |pos, i| {
+                        let current = *pos;
+                        let angle = turn * i as f64;
+                        *pos = (pos.0 + side * angle.cos(), pos.1 + side * angle.sin());
+                        Some(current)
+                    })
+                    .collect()
+            }
+
+            Shape::Spiral {
+                radius,
+                turns,
+                clockwise,
+            } => {
+                let count = (CURVE_RESOLUTION as f64 * turns).ceil() as usize;
+                (0..=count)
+                    .map(|i| i as f64 / count as f64)
+                    .map(|t| {
+                        let angle = direction(clockwise) * 2.0 * PI * turns * t;
+                        (radius * t * angle.cos(), radius * t * angle.sin())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Get the angular direction multiplier for the given drawing direction.
+fn direction(clockwise: bool) -> f64 {
+    if clockwise {
+        -1.0
+    } else {
+        1.0
+    }
+}
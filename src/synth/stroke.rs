@@ -0,0 +1,189 @@
+use rand::Rng;
+
+use super::Shape;
+use config::synth::{FRAME_RATE, PALM_DISTANCE, SPEED};
+use sensor::{FingerFrame, Frame, HandFrame};
+use types::{FingerType, Point3};
+
+/// A synthetic stroke, drawing a shape like a finger would.
+///
+/// The shape is drawn in the XY plane, at a given speed with optional speed variation, and may
+/// be scaled, rotated and made jittery to simulate a realistically drawn gesture.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stroke {
+    /// The shape to draw.
+    shape: Shape,
+
+    /// The factor the shape is scaled by.
+    scale: f64,
+
+    /// The rotation in radians the shape is drawn at.
+    rotation: f64,
+
+    /// The maximum random offset in millimeters applied to each point on each axis.
+    jitter: f64,
+
+    /// The drawing speed in millimeters per second.
+    speed: f64,
+
+    /// The maximum relative random deviation from the drawing speed for each frame.
+    speed_variation: f64,
+}
+
+impl Stroke {
+    /// Construct a new stroke for the given shape.
+    ///
+    /// The stroke is unscaled, unrotated and without any noise by default.
+    pub fn new(shape: Shape) -> Self {
+        Stroke {
+            shape,
+            scale: 1.0,
+            rotation: 0.0,
+            jitter: 0.0,
+            speed: SPEED,
+            speed_variation: 0.0,
+        }
+    }
+
+    /// Set the factor to scale the shape by.
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the rotation in radians to draw the shape at.
+    pub fn rotation(mut self, rotation: f64) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set the maximum random offset in millimeters for each point on each axis.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the drawing speed in millimeters per second, with the maximum relative random
+    /// deviation for each frame.
+    ///
+    /// A `variation` of `0.25` varies the speed between 75% and 125% of the given speed.
+    ///
+    /// This panics if the `speed` isn't above zero, as the stroke would never advance.
+    pub fn speed(mut self, speed: f64, variation: f64) -> Self {
+        assert!(speed > 0.0, "stroke speed must be above zero");
+        self.speed = speed;
+        self.speed_variation = variation;
+        self
+    }
+
+    /// Generate the finger tip points for this stroke, relative to the given `origin`.
+    ///
+    /// One point is produced for each frame at the rate defined in `config::synth::FRAME_RATE`.
+    pub fn points<R: Rng>(&self, origin: Point3, rng: &mut R) -> Vec<Point3> {
+        // Transform the shape outline
+        let (sin, cos) = self.rotation.sin_cos();
+        let outline: Vec<(f64, f64)> = self
+            .shape
+            .outline()
+            .into_iter()
+            .map(|(x, y)| (x * self.scale, y * self.scale))
+            .map(|(x, y)| (x * cos - y * sin, x * sin + y * cos))
+            .collect();
+
+        // Walk along the outline, taking a step each frame
+        let mut points = Vec::new();
+        let mut pos = match outline.first() {
+            Some(first) => *first,
+            None => return Vec::new(),
+        };
+        points.push(pos);
+        let mut step = self.step(rng);
+        for &target in outline.iter().skip(1) {
+            loop {
+                let (dx, dy) = (target.0 - pos.0, target.1 - pos.1);
+                let remaining = (dx * dx + dy * dy).sqrt();
+
+                // Continue on the next outline segment if this one is too short for the step
+                if remaining < step {
+                    step -= remaining;
+                    pos = target;
+                    break;
+                }
+
+                pos = (pos.0 + dx / remaining * step, pos.1 + dy / remaining * step);
+                points.push(pos);
+                step = self.step(rng);
+            }
+        }
+
+        // Always end at the final point of the outline
+        if points.last() != Some(&pos) {
+            points.push(pos);
+        }
+
+        // Apply jitter, and move to the origin
+        points
+            .into_iter()
+            .map(|(x, y)| {
+                Point3::new(
+                    origin.x + x + self.noise(rng),
+                    origin.y + y + self.noise(rng),
+                    origin.z + self.noise(rng),
+                )
+            })
+            .collect()
+    }
+
+    /// Generate sensor frames for this stroke, drawn with the extended index finger of the hand
+    /// with the given `hand_id`. The palm follows the finger tip at `PALM_DISTANCE`.
+    ///
+    /// The first frame has the given `timestamp` in microseconds.
+    pub fn frames<R: Rng>(
+        &self,
+        hand_id: i32,
+        origin: Point3,
+        timestamp: i64,
+        rng: &mut R,
+    ) -> Vec<Frame> {
+        let interval = (1_000_000.0 / FRAME_RATE) as i64;
+        self.points(origin, rng)
+            .into_iter()
+            .enumerate()
+            .map(|(i, tip)| {
+                Frame::new(
+                    timestamp + i as i64 * interval,
+                    vec![HandFrame::new(
+                        hand_id,
+                        vec![FingerFrame::new(FingerType::Index, true, tip)],
+                    )
+                    .with_palm(Point3::new(
+                        tip.x,
+                        tip.y,
+                        tip.z + PALM_DISTANCE,
+                    ))],
+                )
+            })
+            .collect()
+    }
+
+    /// Determine the distance to advance for the next frame.
+    ///
+    /// The speed is never varied below 10% of the configured speed, to keep advancing.
+    fn step<R: Rng>(&self, rng: &mut R) -> f64 {
+        let variation = if self.speed_variation > 0.0 {
+            rng.gen_range(-self.speed_variation, self.speed_variation)
+        } else {
+            0.0
+        };
+        self.speed * (1.0 + variation).max(0.1) / FRAME_RATE
+    }
+
+    /// Generate a random point offset for a single axis.
+    fn noise<R: Rng>(&self, rng: &mut R) -> f64 {
+        if self.jitter > 0.0 {
+            rng.gen_range(-self.jitter, self.jitter)
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::{self, Display};
+
+/// The type of a finger on a hand.
+///
+/// Fingers are ordered from the thumb to the pinky.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum FingerType {
+    /// The thumb.
+    Thumb,
+
+    /// The index finger.
+    Index,
+
+    /// The middle finger.
+    Middle,
+
+    /// The ring finger.
+    Ring,
+
+    /// The pinky finger.
+    Pinky,
+}
+
+impl FingerType {
+    /// Get the finger type for the given numeric finger ID.
+    ///
+    /// Fingers are numbered from `0` for the thumb up to `4` for the pinky, like Leap Motion does.
+    /// `None` is returned for unknown IDs.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(FingerType::Thumb),
+            1 => Some(FingerType::Index),
+            2 => Some(FingerType::Middle),
+            3 => Some(FingerType::Ring),
+            4 => Some(FingerType::Pinky),
+            _ => None,
+        }
+    }
+
+    /// Get the finger name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FingerType::Thumb => "thumb",
+            FingerType::Index => "index",
+            FingerType::Middle => "middle",
+            FingerType::Ring => "ring",
+            FingerType::Pinky => "pinky",
+        }
+    }
+}
+
+impl Display for FingerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
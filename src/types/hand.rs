@@ -0,0 +1,219 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use config::pose::{GRAB_THRESHOLD, PINCH_THRESHOLD};
+use sensor::HandFrame;
+use types::FingerType;
+
+/// A part of a hand that may be traced.
+///
+/// Parts are ordered by finger type, with the palm last.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HandPart {
+    /// The tip of the given finger.
+    Finger(FingerType),
+
+    /// The palm, for whole-hand gestures such as swipes and waves.
+    Palm,
+}
+
+impl Display for HandPart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandPart::Finger(finger) => write!(f, "{} finger", finger),
+            HandPart::Palm => write!(f, "palm"),
+        }
+    }
+}
+
+/// The side of a hand, being the left or right hand.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HandSide {
+    Left,
+    Right,
+}
+
+impl HandSide {
+    /// Get the side name.
+    pub fn name(self) -> &'static str {
+        match self {
+            HandSide::Left => "left",
+            HandSide::Right => "right",
+        }
+    }
+}
+
+impl Display for HandSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HandSide {
+    type Err = String;
+
+    /// Parse a hand side, being `left` or `right`.
+    fn from_str(side: &str) -> Result<Self, Self::Err> {
+        match side {
+            "left" => Ok(HandSide::Left),
+            "right" => Ok(HandSide::Right),
+            _ => Err(format!(
+                "invalid hand side '{}', must be 'left' or 'right'",
+                side
+            )),
+        }
+    }
+}
+
+/// How a template applies to the left and right hand, relative to the hand it was recorded with.
+///
+/// Hands of which the side is unknown are matched as if they are the recorded hand, as are all
+/// hands if the recorded hand is unknown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SideMode {
+    /// The template may be performed with either hand, as recorded.
+    Any,
+
+    /// The template may only be performed with the recorded hand.
+    Only,
+
+    /// The template may be performed with either hand, mirrored for the other hand.
+    Mirror,
+}
+
+impl SideMode {
+    /// Decide how a trace recorded with a hand on the `recorded` side applies to a hand on the
+    /// given `side`.
+    ///
+    /// Returns whether the trace must be mirrored to match, or `None` if it doesn't apply.
+    pub fn mirrored(self, recorded: Option<HandSide>, side: Option<HandSide>) -> Option<bool> {
+        match (self, recorded, side) {
+            (SideMode::Any, _, _) | (_, None, _) | (_, _, None) => Some(false),
+            (_, Some(recorded), Some(side)) if recorded == side => Some(false),
+            (SideMode::Only, _, _) => None,
+            (SideMode::Mirror, _, _) => Some(true),
+        }
+    }
+}
+
+impl Default for SideMode {
+    fn default() -> Self {
+        SideMode::Any
+    }
+}
+
+impl FromStr for SideMode {
+    type Err = String;
+
+    /// Parse a side mode, being `any`, `only` or `mirror`.
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "any" => Ok(SideMode::Any),
+            "only" => Ok(SideMode::Only),
+            "mirror" => Ok(SideMode::Mirror),
+            _ => Err(format!(
+                "invalid side mode '{}', must be 'any', 'only' or 'mirror'",
+                mode
+            )),
+        }
+    }
+}
+
+/// The pose of a hand, at a single moment in time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HandPose {
+    /// The pinch strength, from `0` up to `1`.
+    pub pinch: f64,
+
+    /// The grab strength, from `0` up to `1`.
+    pub grab: f64,
+
+    /// The fingers that are extended.
+    pub extended: Vec<FingerType>,
+}
+
+impl HandPose {
+    /// Get the pose of the given sensor hand frame.
+    pub fn from_frame(hand: &HandFrame) -> Self {
+        Self {
+            pinch: hand.pinch,
+            grab: hand.grab,
+            extended: hand
+                .fingers
+                .iter()
+                .filter(|f| f.extended)
+                .map(|f| f.finger_type)
+                .collect(),
+        }
+    }
+
+    /// Check whether the hand is pinching, as configured by `config::pose::PINCH_THRESHOLD`.
+    pub fn is_pinching(&self) -> bool {
+        self.pinch >= PINCH_THRESHOLD
+    }
+
+    /// Check whether the hand is grabbing, as configured by `config::pose::GRAB_THRESHOLD`.
+    pub fn is_grabbing(&self) -> bool {
+        self.grab >= GRAB_THRESHOLD
+    }
+}
+
+/// Constraints on the pose of a hand, for a template to be detected.
+///
+/// The default constraints allow any pose.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoseConstraints {
+    /// Whether the hand must be pinching.
+    pub pinch: bool,
+
+    /// Whether the hand must be grabbing.
+    pub grab: bool,
+
+    /// The number of fingers that must be extended, if constrained.
+    pub extended: Option<usize>,
+}
+
+impl PoseConstraints {
+    /// Check whether the given hand `pose` satisfies these constraints.
+    pub fn allows(&self, pose: &HandPose) -> bool {
+        (!self.pinch || pose.is_pinching())
+            && (!self.grab || pose.is_grabbing())
+            && self.extended.map_or(true, |n| pose.extended.len() == n)
+    }
+}
+
+impl FromStr for PoseConstraints {
+    type Err = String;
+
+    /// Parse pose constraints, being `any` or a comma separated list of `pinch`, `grab` and
+    /// `fingers=<count>`. For example: `pinch,fingers=2`.
+    fn from_str(constraints: &str) -> Result<Self, Self::Err> {
+        let mut result = Self::default();
+        if constraints == "any" {
+            return Ok(result);
+        }
+
+        for constraint in constraints.split(',') {
+            match constraint {
+                "pinch" => result.pinch = true,
+                "grab" => result.grab = true,
+                _ if constraint.starts_with("fingers=") => {
+                    result.extended = Some(
+                        constraint["fingers=".len()..]
+                            .parse()
+                            .map_err(|_| format!("invalid finger count in '{}'", constraint))?,
+                    );
+                }
+                _ => {
+                    return Err(format!(
+                    "invalid pose constraint '{}', must be 'pinch', 'grab' or 'fingers=<count>'",
+                    constraint,
+                ))
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
@@ -1,14 +1,26 @@
 //! Types used throughout the crate
 
+pub mod finger;
+pub mod hand;
 pub mod model;
+pub mod plane;
 pub mod point;
+pub mod posture;
+pub mod score;
 pub mod template;
 pub mod trace;
+pub mod zone;
 
 /// Re-exports
-pub use self::model::Model;
-pub use self::template::Template;
+pub use self::finger::FingerType;
+pub use self::hand::{HandPart, HandPose, HandSide, PoseConstraints, SideMode};
+pub use self::model::{Model, ModelTrace};
+pub use self::plane::{Plane, PlaneMode};
+pub use self::posture::PostureTemplate;
+pub use self::score::MatchScore;
+pub use self::template::{Template, TemplateOptions};
 pub use self::{
-    point::{Point3, RotPoint},
-    trace::{PointTrace, RotTrace},
+    point::{Point3, RotPoint, TimedPoint3},
+    trace::{PointTrace, RotTrace, RotTraceStream},
+    zone::Zone,
 };
@@ -1,200 +1,172 @@
-use std::cmp::{max, min};
-
-use config::recognition::{
-    INTERRUPT_MARGIN, MARGIN, MAX_DEVIATION_FACTOR, MAX_ERROR, SEARCH_SPACE,
-};
-use types::RotTrace;
+use types::{HandSide, MatchScore, RotTrace};
 
+/// A model, holding one or more traces that together describe a gesture.
+///
+/// Models with multiple traces describe gestures performed with multiple hands, such as
+/// two-handed gestures. Each trace must then be matched for the gesture to be detected.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "ModelData")]
 pub struct Model {
-    /// The model trace
-    ///
-    /// This will be extended to support multiple traces (for a single hand) in the future.
-    trace: RotTrace,
+    /// The model traces.
+    traces: Vec<ModelTrace>,
 }
 
 impl Model {
-    /// Construct a new model.
+    /// Construct a new model with a single trace.
     pub fn new(trace: RotTrace) -> Self {
-        Model { trace }
+        Self::from_traces(vec![ModelTrace::new("trace".into(), 0, None, 0, trace)])
     }
 
-    /// Construct a new empty model.
-    pub fn empty() -> Self {
-        Self::new(RotTrace::empty())
+    /// Construct a new model with the given traces.
+    pub fn from_traces(traces: Vec<ModelTrace>) -> Self {
+        Model { traces }
     }
 
-    /// Get a mutable reference to the internal rotational trace.
-    pub fn trace_mut(&mut self) -> &mut RotTrace {
-        &mut self.trace
+    /// Get the model traces.
+    pub fn traces(&self) -> &[ModelTrace] {
+        &self.traces
     }
 
-    /// Get the number of points in this trace.
-    pub fn len(&self) -> usize {
-        self.trace.len()
+    /// Check whether this model has multiple traces.
+    pub fn is_multi(&self) -> bool {
+        self.traces.len() > 1
     }
 
-    /// Clear the model.
+    /// Check whether this model is empty, having no traces or a trace without points.
     ///
-    /// This resets the model back to zero trace items.
-    pub fn clear(&mut self) {
-        self.trace.clear();
-    }
-
-    /// Trim the model to the given bounds `[from, to]`.
-    pub fn trim(&mut self, from: usize, to: usize) {
-        self.trace.trim(from, to);
+    /// Empty models don't describe a gesture, and can't be matched.
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty() || self.traces.iter().any(|t| t.trace.len() == 0)
     }
 
     /// Compare this model against the given `other` model, and decide whether they are similar
     /// enough to match.
     ///
-    /// The current model must be part of the `other` given model as a whole.
-    /// Thus it is alright if the given `other` model is longer than this model.
-    /// It probably won't match if the given `other` model is much shorter.
-    ///
-    /// This is used for gesture detection.
+    /// See `score`.
     #[inline]
-    pub fn matches(&self, other: &Model) -> bool {
-        // Get iterators over the points, from the end
-        let model = self.trace.points().iter().rev();
-        let other = other.trace.points().iter().rev();
-
-        // Cumulative rotations in iterators
-        let model = model.scan(0.0, |acc, p| {
-            *acc += p.radians();
-            Some(*acc)
-        });
-        let other = other.scan(0.0, |acc, p| {
-            *acc += p.radians();
-            Some(*acc)
-        });
-
-        // Collect all other points for now
-        let other: Vec<f64> = other.collect();
-
-        // The current search position and error count
-        let mut pos = 0;
-        let mut err = 0;
-
-        // Loop through all model points
-        for (p_pos, p) in model.enumerate() {
-            // Make sure the search position doesn't advance too quickly or slowly
-            // It should be in bound relative to the current template point index,
-            // and must have a max deviation as specified in `MAX_DEVIATION_FACTOR`
-            pos = max(
-                min(pos, (p_pos as f64 * MAX_DEVIATION_FACTOR) as usize),
-                (p_pos as f64 / MAX_DEVIATION_FACTOR) as usize,
-            );
-
-            // Find the relative index of the next point close enough to the current template point
-            // from `pos` in the `other` iterator with a search space specified in `SEARCH_SPACE`.
-            // The search will be cancelled if points differ too much as specified in `INTERRUPT_MARGIN`.
-            // If other ends or no valid point is found, `None` will be produced.
-            let offset = other
-                .iter()
-                .skip(pos)
-                .take(SEARCH_SPACE)
-                .map(|o| (o - p).abs())
-                // Interrupt search when maximum margin is exceeded
-                .take_while(|o| o <= &INTERRUPT_MARGIN)
-                // Find point similar enough to template
-                .position(|o| o <= MARGIN);
-
-            // Handle the result that was found, increase the search position by offset
-            match offset {
-                Some(offset) => pos += offset,
-                None => {
-                    err += 1;
-                    if err >= MAX_ERROR {
-                        return false;
-                    }
-                }
-            }
+    pub fn matches(&self, other: &Model, spatial: bool) -> bool {
+        self.score(other, spatial).is_match()
+    }
+
+    /// Score this model against the given `other` model, describing how similar they are.
+    ///
+    /// Each trace is scored against the trace of the `other` model at the same index, see
+    /// `RotTrace::score`, and the worst score is returned. Models with a different number of
+    /// traces don't match at all.
+    pub fn score(&self, other: &Model, spatial: bool) -> MatchScore {
+        let count = self.traces.iter().map(|t| t.trace.len()).sum();
+        if self.traces.len() != other.traces.len() {
+            return MatchScore::mismatch(count);
+        }
+        let mut scores = self
+            .traces
+            .iter()
+            .zip(&other.traces)
+            .map(|(model, other)| model.trace.score(&other.trace, spatial));
+        match scores.next() {
+            Some(first) => scores.fold(first, MatchScore::worst),
+            None => MatchScore::mismatch(count),
+        }
+    }
+}
+
+/// A named trace in a model.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModelTrace {
+    /// The trace name, describing what it was traced with.
+    name: String,
+
+    /// The index of the hand this trace is performed with.
+    ///
+    /// Traces with the same index must be performed with the same hand, traces with a different
+    /// index with different hands.
+    hand: usize,
+
+    /// The side of the hand this trace was recorded with, if known.
+    #[serde(default)]
+    side: Option<HandSide>,
+
+    /// The time in microseconds this trace ends before the last ending trace in the model.
+    offset: i64,
+
+    /// The rotational trace.
+    trace: RotTrace,
+}
+
+impl ModelTrace {
+    /// Construct a new model trace.
+    pub fn new(
+        name: String,
+        hand: usize,
+        side: Option<HandSide>,
+        offset: i64,
+        trace: RotTrace,
+    ) -> Self {
+        Self {
+            name,
+            hand,
+            side,
+            offset,
+            trace,
+        }
+    }
+
+    /// Get the index of the hand this trace is performed with.
+    pub fn hand(&self) -> usize {
+        self.hand
+    }
+
+    /// Get the side of the hand this trace was recorded with, if known.
+    pub fn side(&self) -> Option<HandSide> {
+        self.side
+    }
+
+    /// Get the time in microseconds this trace ends before the last ending trace in the model.
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Get the rotational trace.
+    pub fn trace(&self) -> &RotTrace {
+        &self.trace
+    }
+}
+
+/// The serialized form of a model.
+///
+/// Models saved before multiple traces were supported have a single `trace` field.
+#[derive(Deserialize)]
+struct ModelData {
+    #[serde(default)]
+    trace: Option<RotTrace>,
+
+    #[serde(default)]
+    traces: Vec<ModelTrace>,
+}
+
+impl From<ModelData> for Model {
+    fn from(data: ModelData) -> Self {
+        match data.trace {
+            Some(trace) if data.traces.is_empty() => Model::new(trace),
+            _ => Model::from_traces(data.traces),
         }
+    }
+}
 
-        true
-
-        // TODO: old matching logic, consider what to keep
-        // // Get the model and other model points
-        // let model_points = self.trace.points();
-        // let other_points = other.trace.points();
-        // let model_count = model_points.len();
-        // let other_count = other_points.len();
-
-        // // Skip if the template has more points than our current trace
-        // if other_count < model_count {
-        //     return false;
-        // }
-
-        // // Determine how many points to process, minimum length wins
-        // let count = min(model_points.len(), other_points.len());
-
-        // // Select the last points based on the determined count to use
-        // let model_points = &model_points[model_count - count..model_count];
-        // let other_points = &other_points[other_count - count..other_count];
-
-        // let model_points_cum: Vec<f64> = model_points
-        //     .iter()
-        //     .scan(0.0, |acc, p| {
-        //         *acc += p.radians();
-        //         Some(*acc)
-        //     })
-        //     .collect();
-        // let other_points_cum: Vec<f64> = other_points
-        //     .iter()
-        //     .scan(0.0, |acc, p| {
-        //         *acc += p.radians();
-        //         Some(*acc)
-        //     })
-        //     .collect();
-
-        // // Calculate the difference for each point
-        // let diff = model_points
-        //     .iter()
-        //     .rev()
-        //     .zip(other_points.iter().rev())
-        //     .map(|(a, b)| rad_diff(b.radians(), a.radians()));
-
-        // // Calculate the cumulative difference for each point
-        // let diff_inc_abs_diff: Vec<f64> = model_points_cum
-        //     .iter()
-        //     .rev()
-        //     .zip(other_points_cum.iter().rev())
-        //     .map(|(a, b)| (a - b).abs())
-        //     .collect();
-
-        // // Calculate the cumulative difference on each point
-        // let cum_diff: Vec<f64> = diff
-        //     .scan(0.0, |acc, p| {
-        //         *acc += p;
-        //         Some(*acc)
-        //     })
-        //     .collect();
-
-        // // Skip if the total difference is too big
-        // if cum_diff.last().unwrap().abs() > TOTAL_DIFF_MAX {
-        //     return false;
-        // }
-
-        // if diff_inc_abs_diff
-        //     .windows(8)
-        //     .any(|p| p.iter().filter(|p| *p > &0.3).count() > 6)
-        // {
-        //     return false;
-        // }
-
-        // // Skip if any of the points has a difference of more than 2
-        // if cum_diff.iter().any(|p| p.abs() > POINT_DIFF_MAX) {
-        //     return false;
-        // }
-
-        // // Skip if each window of 5 points has an average difference bigger than 1
-        // if GROUP_SIZE > 0 && cum_diff
-        //     .windows(GROUP_SIZE)
-        //     .any(|p| (p.iter().sum::<f64>().abs() / GROUP_SIZE as f64) > GROUP_DIFF_MAX)
-        // {
-        //     return false;
-        // }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use types::RotPoint;
+
+    #[test]
+    fn single_trace_compat() {
+        let model: Model =
+            serde_json::from_str(r#"{"trace":{"points":[{"angle":0.5,"distance":2.0}]}}"#).unwrap();
+        assert_eq!(
+            model,
+            Model::new(RotTrace::new(vec![RotPoint::new(0.5, 2.0)])),
+        );
+        assert!(!model.is_multi());
     }
 }
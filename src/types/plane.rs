@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nalgebra::geometry::Point3 as NPoint3;
+use nalgebra::{Matrix3, Vector3};
+
+use config::plane::{MIN_PLANARITY, MIN_POINTS, MIN_VARIANCE, VIEW_DIRECTION};
+use types::Point3;
+
+/// A drawing plane, that points are projected onto before computing rotational traces.
+///
+/// Only the orientation of the plane is relevant, as rotational traces don't depend on where
+/// points are or how they are rotated within the plane. The normal of a plane always faces the
+/// user, as described by `config::plane::VIEW_DIRECTION`, so traces are seen from the side the
+/// user sees them from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane {
+    /// The normal of the plane, facing the user.
+    normal: Vector3<f64>,
+
+    /// The horizontal axis within the plane.
+    u: Vector3<f64>,
+
+    /// The vertical axis within the plane.
+    v: Vector3<f64>,
+}
+
+impl Plane {
+    /// The XY plane, in which sensor calibration places the drawing plane.
+    pub fn xy() -> Self {
+        Self {
+            normal: Vector3::z(),
+            u: Vector3::x(),
+            v: Vector3::y(),
+        }
+    }
+
+    /// Construct a plane with the given `normal`, which is flipped to face the user.
+    ///
+    /// The horizontal axis is the X axis projected onto the plane, or the Y axis if the plane is
+    /// perpendicular to it. If the normal has no length, `None` is returned.
+    pub fn from_normal(normal: Vector3<f64>) -> Option<Self> {
+        let mut normal = normal.try_normalize(1.0e-9)?;
+        let view = Vector3::new(VIEW_DIRECTION[0], VIEW_DIRECTION[1], VIEW_DIRECTION[2]);
+        if normal.dot(&view) < 0.0 {
+            normal = -normal;
+        }
+
+        let u = [Vector3::x(), Vector3::y()]
+            .iter()
+            .filter_map(|axis| (axis - normal * normal.dot(axis)).try_normalize(0.1))
+            .next()?;
+        Some(Self {
+            normal,
+            u,
+            v: normal.cross(&u),
+        })
+    }
+
+    /// Estimate the best fitting plane through the given points, using principal component
+    /// analysis.
+    ///
+    /// The normal is the direction the points vary least in. If there are fewer points than
+    /// `config::plane::MIN_POINTS`, or if they don't span a plane clearly enough as described
+    /// by `config::plane::MIN_VARIANCE` and `config::plane::MIN_PLANARITY`, such as for a
+    /// straight line, `None` is returned.
+    pub fn fit(points: &[Point3]) -> Option<Self> {
+        if points.len() < MIN_POINTS {
+            return None;
+        }
+
+        // Build the covariance matrix of the points around their centroid
+        let count = points.len() as f64;
+        let centroid = points
+            .iter()
+            .fold(Vector3::zeros(), |sum, p| sum + p.to_npoint().coords)
+            / count;
+        let covariance = points
+            .iter()
+            .map(|p| p.to_npoint().coords - centroid)
+            .fold(Matrix3::zeros(), |sum, d| sum + d * d.transpose())
+            / count;
+
+        // Take the direction of least variance, if the points span a plane
+        let eigen = covariance.symmetric_eigen();
+        let mut order = [0, 1, 2];
+        order.sort_by(|&a, &b| {
+            eigen.eigenvalues[a]
+                .partial_cmp(&eigen.eigenvalues[b])
+                .expect("failed to compare plane variances")
+        });
+        let (least, middle) = (eigen.eigenvalues[order[0]], eigen.eigenvalues[order[1]]);
+        if middle < MIN_VARIANCE || middle < least * MIN_PLANARITY {
+            return None;
+        }
+        Self::from_normal(eigen.eigenvectors.column(order[0]).into_owned())
+    }
+
+    /// Get the angle in radians between the normals of this and the `other` plane.
+    pub fn angle_to(&self, other: &Plane) -> f64 {
+        self.normal.dot(&other.normal).min(1.0).max(-1.0).acos()
+    }
+
+    /// Transform the given point into the coordinate space of this plane.
+    ///
+    /// The X and Y coordinates are the point projected onto the plane, the Z coordinate is the
+    /// distance to the plane along its normal.
+    #[inline]
+    pub fn project(&self, point: &NPoint3<f64>) -> NPoint3<f64> {
+        NPoint3::new(
+            self.u.dot(&point.coords),
+            self.v.dot(&point.coords),
+            self.normal.dot(&point.coords),
+        )
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self::xy()
+    }
+}
+
+/// The drawing plane a template is traced in.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaneMode {
+    /// Points are projected onto the XY plane, facing the user.
+    Fixed,
+
+    /// Points are projected onto the plane that best fits the recent points of a trace.
+    ///
+    /// This allows a gesture to be drawn in any plane, such as flat over a sensor on a desk.
+    Fitted,
+}
+
+impl Default for PlaneMode {
+    fn default() -> Self {
+        PlaneMode::Fixed
+    }
+}
+
+impl fmt::Display for PlaneMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlaneMode::Fixed => write!(f, "fixed"),
+            PlaneMode::Fitted => write!(f, "fitted"),
+        }
+    }
+}
+
+impl FromStr for PlaneMode {
+    type Err = String;
+
+    /// Parse a plane mode, being `fixed` or `fitted`.
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "fixed" => Ok(PlaneMode::Fixed),
+            "fitted" => Ok(PlaneMode::Fitted),
+            _ => Err(format!(
+                "invalid plane mode '{}', must be 'fixed' or 'fitted'",
+                mode
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_plane() {
+        // A circle drawn flat over the sensor, seen from above
+        let circle: Vec<Point3> = (0..40)
+            .map(|i| i as f64 / 40.0 * 2.0 * ::std::f64::consts::PI)
+            .map(|a| Point3::new(50.0 * a.cos(), 200.0 + (a * 7.0).sin(), 50.0 * a.sin()))
+            .collect();
+        let plane = Plane::fit(&circle).unwrap();
+        assert!(plane.angle_to(&Plane::from_normal(Vector3::y()).unwrap()) < 0.05);
+
+        // Projecting keeps the shape, seen from above with the user at the positive Z side
+        let projected = plane.project(&Point3::new(10.0, 200.0, -20.0).to_npoint());
+        assert!((projected.x - 10.0).abs() < 1.0 && (projected.y - 20.0).abs() < 1.0);
+
+        // The XY plane is fitted as is, lines and too few points have no plane
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let points: Vec<Point3> = (0..20)
+            .map(|i| square[i % 4])
+            .map(|(x, y)| Point3::new(x * 30.0, y * 30.0, 5.0))
+            .collect();
+        assert!(Plane::fit(&points).unwrap().angle_to(&Plane::xy()) < 1.0e-6);
+        let line: Vec<Point3> = (0..20)
+            .map(|i| Point3::new(i as f64, i as f64 * 2.0, 0.0))
+            .collect();
+        assert_eq!(Plane::fit(&line), None);
+        assert_eq!(Plane::fit(&points[..MIN_POINTS - 1]), None);
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt;
 
+#[cfg(feature = "leap")]
 use leap::vector::Vector;
 use nalgebra::geometry::Point3 as NPoint3;
 
 /// A point in 3D space.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Point3 {
     /// The X coordinate.
     pub x: f64,
@@ -29,6 +30,7 @@ impl Point3 {
     }
 
     /// Create a new Point3 using a `&leap::vector::Vector`.
+    #[cfg(feature = "leap")]
     pub fn from_leap(v: &Vector) -> Point3 {
         Self::new(v.x().into(), v.y().into(), v.z().into())
     }
@@ -40,26 +42,55 @@ impl Point3 {
     }
 }
 
+/// A point in 3D space, with the time it was sensed at.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TimedPoint3 {
+    /// The point.
+    pub point: Point3,
+
+    /// The timestamp in microseconds of the sensor frame the point was sensed in.
+    pub timestamp: i64,
+}
+
+impl TimedPoint3 {
+    /// Construct a new timed point.
+    pub fn new(point: Point3, timestamp: i64) -> Self {
+        Self { point, timestamp }
+    }
+}
+
 /// A rotational point.
+///
+/// This holds the turn within the drawing plane (yaw) between two consecutive edges of a trace,
+/// and the pitch of the edge out of the drawing plane to keep the spatial shape of a trace.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct RotPoint {
     /// The angle for this rotation.
     angle: f64,
 
+    /// The pitch of the edge to the next point out of the drawing plane, in radians.
+    ///
+    /// Positive towards the user. Traces saved before pitch was tracked have a zero pitch.
+    #[serde(default)]
+    pitch: f64,
+
     /// The distance to the next point.
     distance: f64,
 }
 
 impl RotPoint {
-    /// Construct a new rotational point.
+    /// Construct a new rotational point, within the drawing plane.
     pub fn new(angle: f64, distance: f64) -> Self {
-        Self { angle, distance }
+        Self::spatial(angle, 0.0, distance)
     }
 
-    /// Construct a new rotational point with it's components in a tuple.
-    /// This may be used for iterators, like: `.map(RotPoint::from_tuple)`
-    pub fn from_tuple((angle, distance): (f64, f64)) -> Self {
-        Self::new(angle, distance)
+    /// Construct a new rotational point, with the given `pitch` out of the drawing plane.
+    pub fn spatial(angle: f64, pitch: f64, distance: f64) -> Self {
+        Self {
+            angle,
+            pitch,
+            distance,
+        }
     }
 
     /// Create a rotational point based on the given number of degrees instead of using radians.
@@ -72,6 +103,23 @@ impl RotPoint {
     pub fn radians(&self) -> f64 {
         self.angle
     }
+
+    /// Get the distance to the next point.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Get the pitch in radians of the edge to the next point out of the drawing plane.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Get the mirrored rotational point, rotating in the opposite direction.
+    ///
+    /// Mirroring is done within the drawing plane, so the pitch is kept.
+    pub fn mirrored(&self) -> Self {
+        Self::spatial(-self.angle, self.pitch, self.distance)
+    }
 }
 
 impl fmt::Display for Point3 {
@@ -82,10 +130,15 @@ impl fmt::Display for Point3 {
 
 impl fmt::Display for RotPoint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({} rad -> {})", self.angle, self.distance)
+        write!(
+            f,
+            "({} rad, {} rad pitch -> {})",
+            self.angle, self.pitch, self.distance
+        )
     }
 }
 
+#[cfg(feature = "leap")]
 impl From<Vector> for Point3 {
     fn from(v: Vector) -> Self {
         Point3::from_leap(&v)
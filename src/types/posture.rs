@@ -0,0 +1,102 @@
+use rand::{thread_rng, RngCore};
+
+use config::posture::HOLD_TIME;
+use types::{FingerType, HandPose, PoseConstraints};
+
+/// A posture template, describing a static hand pose.
+///
+/// A posture is detected when a hand holds a matching pose for the posture hold time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PostureTemplate {
+    id: u32,
+    name: String,
+
+    /// The exact set of fingers that must be extended, if constrained.
+    fingers: Option<Vec<FingerType>>,
+
+    /// Additional constraints on the hand pose.
+    #[serde(default)]
+    pose: PoseConstraints,
+
+    /// The time in microseconds the posture must be held for to be detected.
+    #[serde(default = "default_hold")]
+    hold: i64,
+}
+
+impl PostureTemplate {
+    /// Construct a new posture template with the given `name`, requiring exactly the given
+    /// extended `fingers` if set, and satisfying the given `pose` constraints.
+    ///
+    /// The posture must be held for `config::posture::HOLD_TIME`. A random ID will be picked for
+    /// this template.
+    pub fn new(name: String, fingers: Option<Vec<FingerType>>, pose: PoseConstraints) -> Self {
+        // Pick a random ID
+        let id = thread_rng().next_u32();
+
+        PostureTemplate {
+            id,
+            name,
+            fingers,
+            pose,
+            hold: default_hold(),
+        }
+    }
+
+    /// Construct a new posture template with the given `name`, describing the given hand `pose`.
+    ///
+    /// The posture requires the same extended fingers, and pinching or grabbing if the hand is.
+    pub fn from_pose(name: String, pose: &HandPose) -> Self {
+        Self::new(
+            name,
+            Some(pose.extended.clone()),
+            PoseConstraints {
+                pinch: pose.is_pinching(),
+                grab: pose.is_grabbing(),
+                extended: None,
+            },
+        )
+    }
+
+    /// Get the template id.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Get the template name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the time in microseconds the posture must be held for to be detected.
+    pub fn hold(&self) -> i64 {
+        self.hold
+    }
+
+    /// Get how specific this posture is, a posture constraining more of the hand pose being more
+    /// specific.
+    ///
+    /// An exact set of extended fingers is more specific than a number of extended fingers.
+    /// Pinch and grab constraints rank postures that are equally specific otherwise.
+    pub fn specificity(&self) -> (u8, u8) {
+        let fingers = match (&self.fingers, self.pose.extended) {
+            (Some(_), _) => 2,
+            (None, Some(_)) => 1,
+            (None, None) => 0,
+        };
+        (fingers, self.pose.pinch as u8 + self.pose.grab as u8)
+    }
+
+    /// Check whether the given hand `pose` matches this posture.
+    pub fn matches(&self, pose: &HandPose) -> bool {
+        let fingers = self.fingers.as_ref().map_or(true, |fingers| {
+            fingers.len() == pose.extended.len()
+                && fingers.iter().all(|f| pose.extended.contains(f))
+        });
+        fingers && self.pose.allows(pose)
+    }
+}
+
+/// The default posture hold time.
+fn default_hold() -> i64 {
+    HOLD_TIME
+}
@@ -0,0 +1,108 @@
+use std::cmp::{max, min};
+
+use config::recognition::MAX_ERROR;
+
+/// The result of comparing a model trace against another trace, describing how similar they are.
+///
+/// Scores allow ranking templates, and telling a near miss from a bad mismatch.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MatchScore {
+    /// The normalized distance between the traces, from `0` for a perfect match to `1`.
+    ///
+    /// This is the mean difference of each model point to its matched point, relative to the
+    /// allowed margin. Model points without a matched point count as `1`.
+    distance: f64,
+
+    /// The number of model points no matching point was found for.
+    errors: usize,
+
+    /// The pairs of matched point indices, in the model trace and the other trace.
+    ///
+    /// These aren't collected if scoring stopped early, because the traces can't match.
+    matched: Vec<(usize, usize)>,
+
+    /// The index of the point in the other trace the match is aligned to start at.
+    offset: usize,
+}
+
+impl MatchScore {
+    /// Construct a new score.
+    pub fn new(distance: f64, errors: usize, matched: Vec<(usize, usize)>, offset: usize) -> Self {
+        Self {
+            distance,
+            errors,
+            matched,
+            offset,
+        }
+    }
+
+    /// Construct a score for traces that don't match at all, none of the `count` model points
+    /// having a matched point.
+    pub fn mismatch(count: usize) -> Self {
+        Self::new(1.0, max(count, MAX_ERROR), Vec::new(), 0)
+    }
+
+    /// Get the normalized distance between the traces, from `0` for a perfect match to `1`.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Get the number of model points no matching point was found for.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Get the pairs of matched point indices, in the model trace and the other trace.
+    pub fn matched(&self) -> &[(usize, usize)] {
+        &self.matched
+    }
+
+    /// Get the index of the point in the other trace the match is aligned to start at.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Check whether the traces are similar enough to match, having fewer errors than
+    /// `config::recognition::MAX_ERROR`.
+    pub fn is_match(&self) -> bool {
+        self.errors < MAX_ERROR
+    }
+
+    /// Check whether this score is better than the `other`, either matching while the other
+    /// doesn't or having a smaller distance.
+    pub fn is_better_than(&self, other: &MatchScore) -> bool {
+        match (self.is_match(), other.is_match()) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => self.distance < other.distance,
+        }
+    }
+
+    /// Get the worst of this score and the `other`, see `is_better_than`.
+    ///
+    /// This combines the scores of multiple traces that must all match.
+    pub fn worst(self, other: MatchScore) -> Self {
+        if other.is_better_than(&self) {
+            self
+        } else {
+            other
+        }
+    }
+
+    /// Map the indices of this score onto the other trace, for a score against `count` points
+    /// resampled from a window of `window` points starting at index `start` of the other trace.
+    pub fn unresampled(mut self, start: usize, window: usize, count: usize) -> Self {
+        let map = |i: usize| {
+            start
+                + min(
+                    ((i as f64 + 0.5) * window as f64 / count as f64) as usize,
+                    window.saturating_sub(1),
+                )
+        };
+        for pair in &mut self.matched {
+            pair.1 = map(pair.1);
+        }
+        self.offset = map(self.offset);
+        self
+    }
+}
@@ -1,23 +1,117 @@
+use std::str::FromStr;
+
 use rand::{thread_rng, RngCore};
 
-use types::Model;
+use types::{
+    FingerType, HandPart, HandPose, HandSide, Model, ModelTrace, PlaneMode, PoseConstraints,
+    SideMode,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Template {
     id: u32,
     name: String,
+
+    /// The fingers this template may be performed with.
+    ///
+    /// Templates saved before fingers could be targeted default to the index finger.
+    #[serde(default = "default_fingers")]
+    fingers: Vec<FingerType>,
+
+    /// Whether this template may be performed with the palm.
+    #[serde(default)]
+    palm: bool,
+
+    /// The constraints on the hand pose this template must be performed in.
+    #[serde(default)]
+    pose: PoseConstraints,
+
+    /// How this template applies to the left and right hand.
+    #[serde(default)]
+    sides: SideMode,
+
+    /// The drawing plane this template is traced in.
+    #[serde(default)]
+    plane: PlaneMode,
+
+    /// Whether this template matches the full 3D shape of traces, including their pitch out of
+    /// the drawing plane.
+    #[serde(default)]
+    spatial: bool,
+
+    /// Whether this template matches traces drawn at any size.
+    ///
+    /// The traces of the model of a scale invariant template are resampled to a fixed number of
+    /// points, see `RotTrace::resample`.
+    #[serde(default)]
+    scale_invariant: bool,
+
     model: Model,
 }
 
 impl Template {
-    /// Construct a new template with the given `name` and `model`.
+    /// Construct a new template with the given `name` and `model`, for the index finger.
     ///
     /// A random ID will be picked for this template.
     pub fn new(name: String, model: Model) -> Self {
+        Self::with_fingers(name, default_fingers(), model)
+    }
+
+    /// Construct a new template with the given `name` and `model`, that may be performed with
+    /// any of the given `fingers`.
+    ///
+    /// A random ID will be picked for this template.
+    pub fn with_fingers(name: String, fingers: Vec<FingerType>, model: Model) -> Self {
+        Self::with_parts(name, fingers, false, model)
+    }
+
+    /// Construct a new template with the given `name` and `model`, for the given hand `parts`.
+    ///
+    /// A random ID will be picked for this template.
+    pub fn for_parts(name: String, parts: &[HandPart], model: Model) -> Self {
+        let mut fingers = vec![];
+        let mut palm = false;
+        for part in parts {
+            match part {
+                HandPart::Finger(finger) if !fingers.contains(finger) => fingers.push(*finger),
+                HandPart::Finger(_) => {}
+                HandPart::Palm => palm = true,
+            }
+        }
+        Self::with_parts(name, fingers, palm, model)
+    }
+
+    /// Construct a new template that may be performed with any of the given `fingers`, or with
+    /// the `palm` if set.
+    fn with_parts(name: String, fingers: Vec<FingerType>, palm: bool, model: Model) -> Self {
         // Pick a random ID
         let id = thread_rng().next_u32();
 
-        Template { id, name, model }
+        Template {
+            id,
+            name,
+            fingers,
+            palm,
+            pose: PoseConstraints::default(),
+            sides: SideMode::default(),
+            plane: PlaneMode::default(),
+            spatial: false,
+            scale_invariant: false,
+            model,
+        }
+    }
+
+    /// Set the options for matching this template.
+    ///
+    /// The traces of the model must be resampled to a fixed number of points if the options make
+    /// the template scale invariant.
+    pub fn with_options(mut self, options: TemplateOptions) -> Self {
+        self.pose = options.pose;
+        self.sides = options.sides;
+        self.plane = options.plane;
+        self.spatial = options.spatial;
+        self.scale_invariant = options.scale_invariant;
+        self
     }
 
     /// Get the template id.
@@ -30,8 +124,109 @@ impl Template {
         &self.name
     }
 
+    /// Check whether this template may be performed with the given hand `part`.
+    #[inline]
+    pub fn applies_to(&self, part: HandPart) -> bool {
+        match part {
+            HandPart::Finger(finger) => self.fingers.contains(&finger),
+            HandPart::Palm => self.palm,
+        }
+    }
+
+    /// Check whether this template may be performed in the given hand `pose`.
+    #[inline]
+    pub fn allows_pose(&self, pose: &HandPose) -> bool {
+        self.pose.allows(pose)
+    }
+
+    /// Decide how the given model `trace` applies to a hand on the given `side`.
+    ///
+    /// Returns whether the trace must be mirrored to match, or `None` if it doesn't apply.
+    #[inline]
+    pub fn mirrored(&self, trace: &ModelTrace, side: Option<HandSide>) -> Option<bool> {
+        self.sides.mirrored(trace.side(), side)
+    }
+
+    /// Get the drawing plane this template is traced in.
+    #[inline]
+    pub fn plane(&self) -> PlaneMode {
+        self.plane
+    }
+
+    /// Check whether this template matches the full 3D shape of traces.
+    #[inline]
+    pub fn spatial(&self) -> bool {
+        self.spatial
+    }
+
+    /// Check whether this template matches traces drawn at any size.
+    #[inline]
+    pub fn scale_invariant(&self) -> bool {
+        self.scale_invariant
+    }
+
     /// Get the template model.
     pub fn model(&self) -> &Model {
         &self.model
     }
 }
+
+/// Options for matching a template, chosen when recording it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateOptions {
+    /// The constraints on the hand pose the template must be performed in.
+    pub pose: PoseConstraints,
+
+    /// How the template applies to the left and right hand.
+    pub sides: SideMode,
+
+    /// The drawing plane the template is traced in.
+    pub plane: PlaneMode,
+
+    /// Whether the template matches the full 3D shape of traces.
+    pub spatial: bool,
+
+    /// Whether the template matches traces drawn at any size.
+    pub scale_invariant: bool,
+}
+
+impl FromStr for TemplateOptions {
+    type Err = String;
+
+    /// Parse template options, being `any` or a comma separated list of pose constraints,
+    /// `sides=<mode>`, `plane=<mode>`, `spatial` and `any-size`. For example:
+    /// `pinch,sides=mirror,any-size`.
+    ///
+    /// See `PoseConstraints`, `SideMode` and `PlaneMode` for their formats.
+    fn from_str(options: &str) -> Result<Self, Self::Err> {
+        let mut result = Self::default();
+        if options == "any" {
+            return Ok(result);
+        }
+
+        let mut pose = vec![];
+        for option in options.split(',') {
+            match option {
+                "spatial" => result.spatial = true,
+                "any-size" => result.scale_invariant = true,
+                _ if option.starts_with("sides=") => {
+                    result.sides = option["sides=".len()..].parse()?
+                }
+                _ if option.starts_with("plane=") => {
+                    result.plane = option["plane=".len()..].parse()?
+                }
+                _ => pose.push(option),
+            }
+        }
+        if !pose.is_empty() {
+            result.pose = pose.join(",").parse()?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// The fingers templates apply to by default.
+fn default_fingers() -> Vec<FingerType> {
+    vec![FingerType::Index]
+}
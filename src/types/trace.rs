@@ -1,30 +1,50 @@
-use std::cmp::max;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::iter;
 
 use itertools::Itertools;
-use nalgebra::geometry;
+use nalgebra::{geometry, Vector2, Vector3};
 use std::fmt;
 
-use config::{recognition::KEEP_POINTS, trace::MAX_POINTS};
+use config::{
+    recognition::{
+        INTERRUPT_MARGIN, KEEP_POINTS, MARGIN, MAX_DEVIATION_FACTOR, MAX_ERROR, PITCH_MARGIN,
+        SEARCH_SPACE,
+    },
+    scale::{MIN_WINDOW, WINDOW_GROWTH},
+    trace::{MAX_POINTS, MIN_PLANAR_RATIO},
+};
 use prelude::*;
-use types::{Point3, RotPoint};
+use types::{MatchScore, Plane, Point3, RotPoint, TimedPoint3};
 use util::rad::diff as rad_diff;
+use util::sampler::StreamSampler;
 
-/// The 2D point type we're using
-type NPoint2 = geometry::Point2<f64>;
+/// The 2D vector type we're using
+type NVector2 = Vector2<f64>;
 
 /// The 3D point type we're using
 type NPoint3 = geometry::Point3<f64>;
 
+/// The 3D vector type we're using
+type NVector3 = Vector3<f64>;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct PointTrace {
-    /// The trace points.
-    points: Vec<Point3>,
+    /// The trace points, with the time they were sensed at.
+    points: Vec<TimedPoint3>,
 }
 
 impl PointTrace {
-    /// Constructor.
+    /// Constructor, for points without timing.
+    ///
+    /// All points get a zero timestamp.
     #[allow(unused)]
     pub fn new(v: Vec<Point3>) -> Self {
+        Self::from_timed(v.into_iter().map(|p| TimedPoint3::new(p, 0)).collect())
+    }
+
+    /// Constructor, for timed points.
+    pub fn from_timed(v: Vec<TimedPoint3>) -> Self {
         Self { points: v }
     }
 
@@ -33,6 +53,11 @@ impl PointTrace {
         Self { points: vec![] }
     }
 
+    /// Get a reference to the timed points in this trace.
+    pub fn points(&self) -> &[TimedPoint3] {
+        &self.points
+    }
+
     /// Given a list of points, calculate the rotation/angle the edges between
     /// points in radians.
     ///
@@ -42,8 +67,9 @@ impl PointTrace {
     ///
     /// TODO: stream the iterator result, don't collect, improve performance
     #[inline]
-    fn calc_rot_points(points: &[Point3]) -> Vec<RotPoint> {
-        Self::calc_rot_points_iter(points.into_iter().map(|p| p.to_npoint())).collect()
+    fn calc_rot_points(points: &[TimedPoint3]) -> Vec<RotPoint> {
+        Self::calc_rot_points_iter(points.into_iter().map(|p| p.point.to_npoint()), Plane::xy())
+            .collect()
     }
 
     /// Given a list of points, calculate the rotation/angle the edges between
@@ -55,19 +81,20 @@ impl PointTrace {
     ///
     /// A streaming/lazy iterator is returned for optimal performance.
     ///
-    /// TODO: dynamically determine drawing plane at `NPoint2` conversion point
+    /// Points are transformed into the space of the given drawing `plane`, see `calc_rot_point`.
+    /// Points must be in the shared coordinate space, in which sensor calibration places the
+    /// drawing plane of each sensor in the XY plane, unless a fitted plane is used.
     #[inline]
-    fn calc_rot_points_iter<'a, I>(points: I) -> impl Iterator<Item = RotPoint> + 'a
+    fn calc_rot_points_iter<'a, I>(points: I, plane: Plane) -> impl Iterator<Item = RotPoint> + 'a
     where
         I: Iterator<Item = NPoint3> + 'a,
     {
         points
-            .map(|p| NPoint2::new(p.x, p.y))
+            .map(move |p| plane.project(&p))
             .tuple_windows()
             .map(|(a, b)| b - a)
             .tuple_windows()
-            .map(|(a, b)| (rad_diff(b.y.atan2(b.x), a.y.atan2(a.x)), a.magnitude()))
-            .map(RotPoint::from_tuple)
+            .map(|(a, b)| calc_rot_point(&a, &b))
     }
 
     /// Given a list of points, calculate the rotation/angle the edges between
@@ -78,11 +105,11 @@ impl PointTrace {
     /// is returned.
     #[inline]
     fn to_rot_points(&self, resample: bool) -> Vec<RotPoint> {
+        let points = self.points.iter().map(|p| p.point.to_npoint());
         if resample {
-            Self::calc_rot_points_iter(self.points.iter().map(|p| p.to_npoint()).sample_points())
-                .collect()
+            Self::calc_rot_points_iter(points.sample_points(), Plane::xy()).collect()
         } else {
-            Self::calc_rot_points_iter(self.points.iter().map(|p| p.to_npoint())).collect()
+            Self::calc_rot_points_iter(points, Plane::xy()).collect()
         }
     }
 
@@ -110,28 +137,113 @@ impl PointTrace {
         RotTrace::new(self.to_rot_points(resample))
     }
 
-    /// Add a new point to the trace.
+    /// Add a new point to the trace, sensed at the given `timestamp` in microseconds.
+    ///
+    /// The number of oldest points that were truncated from the trace is returned.
     #[inline]
-    pub fn push(&mut self, point: Point3) {
-        self.points.push(point);
-        self.truncate();
+    pub fn push(&mut self, point: Point3, timestamp: i64) -> usize {
+        self.points.push(TimedPoint3::new(point, timestamp));
+        self.truncate()
     }
 
     /// Truncate the trace to the maximum allowed points.
     ///
     /// This removes the oldest points from the trace to fit `config::trace::MAX_POINTS`.
     /// If the maximum isn't reached yet, invoking this does nothing.
+    /// The number of truncated points is returned.
     ///
-    /// TODO: do not apply this when recording a trace, as it may have any
-    /// length.
+    /// Points aren't truncated by age here, as a trace that is being recorded may take any time.
+    /// See `truncate_age` instead.
     #[inline]
-    fn truncate(&mut self) {
-        if self.points.len() > MAX_POINTS {
-            let truncate = self.points.len() - MAX_POINTS;
-            self.points.drain(..truncate);
+    fn truncate(&mut self) -> usize {
+        if self.points.len() <= MAX_POINTS {
+            return 0;
+        }
+        let truncate = self.points.len() - MAX_POINTS;
+        self.points.drain(..truncate);
+        truncate
+    }
+
+    /// Truncate the trace to the points sensed within the given `max_age` in microseconds,
+    /// relative to the newest point.
+    /// The number of truncated points is returned.
+    ///
+    /// For example, a `max_age` of `2_000_000` keeps the last 2 seconds of the trace.
+    pub fn truncate_age(&mut self, max_age: i64) -> usize {
+        let newest = match self.points.last() {
+            Some(point) => point.timestamp,
+            None => return 0,
+        };
+        let truncate = self
+            .points
+            .iter()
+            .take_while(|p| newest - p.timestamp > max_age)
+            .count();
+        self.points.drain(..truncate);
+        truncate
+    }
+
+    /// Get the duration of the trace in microseconds, from the oldest to the newest point.
+    pub fn duration(&self) -> i64 {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => last.timestamp - first.timestamp,
+            _ => 0,
         }
     }
 
+    /// Get the length of the path along all trace points in millimeters.
+    pub fn length(&self) -> f64 {
+        self.points
+            .iter()
+            .map(|p| p.point.to_npoint())
+            .tuple_windows()
+            .map(|(a, b)| (b - a).norm())
+            .sum()
+    }
+
+    /// Get the average velocity along the trace in millimeters per second.
+    ///
+    /// If the trace has no duration, `None` is returned instead.
+    pub fn velocity(&self) -> Option<f64> {
+        match self.duration() {
+            0 => None,
+            duration => Some(self.length() * 1_000_000.0 / duration as f64),
+        }
+    }
+
+    /// Get the average velocity in millimeters per second along the points sensed within the
+    /// given `window` in microseconds, relative to the newest point.
+    ///
+    /// If these points have no duration, `None` is returned instead.
+    pub fn recent_velocity(&self, window: i64) -> Option<f64> {
+        PointTrace::from_timed(self.recent(window).to_vec()).velocity()
+    }
+
+    /// Fit a drawing plane through the points sensed within the given `window` in microseconds,
+    /// relative to the newest point.
+    ///
+    /// If these points don't span a plane, `None` is returned instead. See `Plane::fit`.
+    pub fn recent_plane(&self, window: i64) -> Option<Plane> {
+        let points: Vec<Point3> = self.recent(window).iter().map(|p| p.point).collect();
+        Plane::fit(&points)
+    }
+
+    /// Get the points sensed within the given `window` in microseconds, relative to the newest
+    /// point.
+    pub fn recent(&self, window: i64) -> &[TimedPoint3] {
+        let newest = match self.points.last() {
+            Some(point) => point.timestamp,
+            None => return &[],
+        };
+        let count = self
+            .points
+            .iter()
+            .rev()
+            .take_while(|p| newest - p.timestamp <= window)
+            .count();
+        &self.points[self.points.len() - count..]
+    }
+
     /// Clear most of the trace, except for the last few (newest) points as
     /// specified in `config::recognition::KEEP_POINTS`.
     ///
@@ -143,6 +255,13 @@ impl PointTrace {
             self.points.drain(..len - KEEP_POINTS);
         }
     }
+
+    /// Clear the trace.
+    ///
+    /// This resets the trace back to zero points.
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -172,6 +291,20 @@ impl RotTrace {
         self.points.len()
     }
 
+    /// Add a new rotational point to the trace.
+    #[inline]
+    pub fn push(&mut self, point: RotPoint) {
+        self.points.push(point);
+    }
+
+    /// Remove the given number of oldest points from the trace.
+    ///
+    /// If the trace has less points, all points are removed.
+    pub fn remove_oldest(&mut self, count: usize) {
+        let count = min(count, self.points.len());
+        self.points.drain(..count);
+    }
+
     /// Trim the trace to the given bounds `[from, to]`.
     ///
     /// The bounds may be greater than the actual trace itself.
@@ -191,6 +324,424 @@ impl RotTrace {
     pub fn clear(&mut self) {
         self.points.clear();
     }
+
+    /// Get the part of the trace within the given bounds `[from, to]`.
+    pub fn slice(&self, from: usize, to: usize) -> RotTrace {
+        RotTrace::new(self.points[from..to].to_vec())
+    }
+
+    /// Get the mirrored trace, as if it was traced in a mirror.
+    ///
+    /// This turns a trace of the left hand into the same trace of the right hand, and the other
+    /// way around.
+    pub fn mirrored(&self) -> RotTrace {
+        RotTrace::new(self.points.iter().map(RotPoint::mirrored).collect())
+    }
+
+    /// Resample the trace to the given number of points, spread evenly over its length.
+    ///
+    /// The total turn of the trace is kept, so traces of the same shape drawn at different sizes
+    /// become alike. This is used for scale invariant matching.
+    pub fn resample(&self, count: usize) -> RotTrace {
+        let len = self.points.len();
+        if len == 0 || count == 0 {
+            return RotTrace::empty();
+        }
+
+        // The cumulative turn at a position along the trace, each point spanning one unit
+        let cumulative: Vec<f64> = iter::once(0.0)
+            .chain(self.points.iter().scan(0.0, |acc, p| {
+                *acc += p.radians();
+                Some(*acc)
+            }))
+            .collect();
+        let turn_at = |pos: f64| {
+            let i = min(pos as usize, len - 1);
+            cumulative[i] + (cumulative[i + 1] - cumulative[i]) * (pos - i as f64)
+        };
+
+        let step = len as f64 / count as f64;
+        let distance = self.points.iter().map(|p| p.distance()).sum::<f64>() / count as f64;
+        RotTrace::new(
+            (0..count)
+                .map(|i| (i as f64 * step, (i + 1) as f64 * step))
+                .map(|(from, to)| {
+                    let pitch = self.points[min(((from + to) / 2.0) as usize, len - 1)].pitch();
+                    RotPoint::spatial(turn_at(to) - turn_at(from), pitch, distance)
+                })
+                .collect(),
+        )
+    }
+
+    /// Compare this scale invariant trace against the given `other` trace at any size, and
+    /// decide whether they are similar enough to match.
+    ///
+    /// See `score_scaled`.
+    pub fn matches_scaled(&self, other: &RotTrace, whole: bool, spatial: bool) -> bool {
+        self.score_scaled(other, whole, spatial)
+            .map_or(false, |score| score.is_match())
+    }
+
+    /// Score this scale invariant trace against the given `other` trace at any size.
+    ///
+    /// This trace must be resampled to a fixed number of points, see `resample`. If `whole` is
+    /// set, the `other` trace is resampled to the same number of points and scored as a whole.
+    /// Otherwise windows at the end of the `other` trace are, with lengths starting at
+    /// `config::scale::MIN_WINDOW` growing by `config::scale::WINDOW_GROWTH`, and the best
+    /// window is scored. Indices in the score refer to points in the `other` trace.
+    ///
+    /// If the `other` trace has no window to score, `None` is returned.
+    pub fn score_scaled(&self, other: &RotTrace, whole: bool, spatial: bool) -> Option<MatchScore> {
+        let (len, count) = (other.len(), self.len());
+        if whole {
+            return self
+                .score_whole(&other.resample(count), spatial)
+                .map(|score| score.unresampled(0, len, count));
+        }
+
+        let mut best: Option<MatchScore> = None;
+        let mut window = MIN_WINDOW;
+        while window <= len {
+            let start = len - window;
+            let score = self
+                .score_whole(&other.slice(start, len).resample(count), spatial)
+                .map(|score| score.unresampled(start, window, count));
+            if let Some(score) = score {
+                if best
+                    .as_ref()
+                    .map_or(true, |best| score.is_better_than(best))
+                {
+                    best = Some(score);
+                }
+            }
+            window = max(window + 1, (window as f64 * WINDOW_GROWTH) as usize);
+        }
+        best
+    }
+
+    /// Compare this trace against the given `other` trace as a whole, and decide whether they are
+    /// similar enough to match.
+    ///
+    /// See `score_whole`.
+    #[inline]
+    pub fn matches_whole(&self, other: &RotTrace, spatial: bool) -> bool {
+        self.score_whole(other, spatial)
+            .map_or(false, |score| score.is_match())
+    }
+
+    /// Score this trace against the given `other` trace as a whole.
+    ///
+    /// Unlike `score`, the `other` trace must not be much longer than this trace, as described
+    /// by `config::recognition::MAX_DEVIATION_FACTOR`, or `None` is returned. This is used to
+    /// match whole strokes.
+    #[inline]
+    pub fn score_whole(&self, other: &RotTrace, spatial: bool) -> Option<MatchScore> {
+        if other.len() as f64 <= self.len() as f64 * MAX_DEVIATION_FACTOR {
+            Some(self.score(other, spatial))
+        } else {
+            None
+        }
+    }
+
+    /// Compare this trace against the given `other` trace, and decide whether they are similar
+    /// enough to match.
+    ///
+    /// See `score`. This is used for gesture detection.
+    #[inline]
+    pub fn matches(&self, other: &RotTrace, spatial: bool) -> bool {
+        self.score(other, spatial).is_match()
+    }
+
+    /// Score this trace against the given `other` trace, describing how similar they are.
+    ///
+    /// The current trace must be part of the `other` given trace as a whole, at its end.
+    /// Thus it is alright if the given `other` trace is longer than this trace.
+    /// It probably won't match if the given `other` trace is much shorter.
+    ///
+    /// If `spatial` is set, the pitch of points out of the drawing plane must match as well
+    /// within `config::recognition::PITCH_MARGIN`, to match the full 3D shape of the traces.
+    ///
+    /// Scoring stops once `config::recognition::MAX_ERROR` errors are found, as the traces can't
+    /// match anymore. The model points that weren't compared then count as unmatched in the
+    /// distance.
+    pub fn score(&self, other: &RotTrace, spatial: bool) -> MatchScore {
+        let (model_len, other_len) = (self.len(), other.len());
+
+        // Get iterators over the points, from the end
+        let model = self.points.iter().rev();
+        let other = other.points.iter().rev();
+
+        // Cumulative rotations in iterators, along with the pitch
+        let model = model.scan(0.0, |acc, p| {
+            *acc += p.radians();
+            Some((*acc, p.pitch()))
+        });
+        let other = other.scan(0.0, |acc, p| {
+            *acc += p.radians();
+            Some((*acc, p.pitch()))
+        });
+
+        // Collect all other points for now
+        let other: Vec<(f64, f64)> = other.collect();
+
+        // The current search position and error count, the summed point differences relative
+        // to the margins, and the matched point indices from the end
+        let mut pos = 0;
+        let mut err = 0;
+        let mut diff = 0.0;
+        let mut matched = Vec::new();
+
+        // Loop through all model points
+        for (p_pos, p) in model.enumerate() {
+            // Check whether a point is similar enough to the current template point
+            let similar = |o: &(f64, f64)| {
+                (o.0 - p.0).abs() <= MARGIN && (!spatial || (o.1 - p.1).abs() <= PITCH_MARGIN)
+            };
+
+            // Make sure the search position doesn't advance too quickly or slowly
+            // It should be in bound relative to the current template point index,
+            // and must have a max deviation as specified in `MAX_DEVIATION_FACTOR`
+            pos = max(
+                min(pos, (p_pos as f64 * MAX_DEVIATION_FACTOR) as usize),
+                (p_pos as f64 / MAX_DEVIATION_FACTOR) as usize,
+            );
+
+            // Find the relative index of the next point close enough to the current template point
+            // from `pos` in the `other` iterator with a search space specified in `SEARCH_SPACE`.
+            // The search will be cancelled if points differ too much as specified in `INTERRUPT_MARGIN`.
+            // If other ends or no valid point is found, `None` will be produced.
+            let offset = other
+                .iter()
+                .skip(pos)
+                .take(SEARCH_SPACE)
+                // Interrupt search when maximum margin is exceeded
+                .take_while(|o| (o.0 - p.0).abs() <= INTERRUPT_MARGIN)
+                // Find point similar enough to template
+                .position(similar);
+
+            // Handle the result that was found, increase the search position by offset
+            match offset {
+                Some(offset) => {
+                    pos += offset;
+                    let o = other[pos];
+                    diff += if spatial {
+                        ((o.0 - p.0).abs() / MARGIN).max((o.1 - p.1).abs() / PITCH_MARGIN)
+                    } else {
+                        (o.0 - p.0).abs() / MARGIN
+                    };
+                    matched.push((model_len - 1 - p_pos, other_len - 1 - pos));
+                }
+                None => {
+                    err += 1;
+                    if err >= MAX_ERROR {
+                        let unscored = model_len - 1 - p_pos;
+                        let distance = (diff + 1.0 + unscored as f64) / model_len as f64;
+                        return MatchScore::new(distance, err, Vec::new(), 0);
+                    }
+                    diff += 1.0;
+                }
+            }
+        }
+
+        // Align the start of this trace through the first matched point
+        matched.reverse();
+        let distance = if model_len > 0 {
+            diff / model_len as f64
+        } else {
+            0.0
+        };
+        let offset = matched
+            .first()
+            .map_or(other_len.saturating_sub(model_len), |&(m, o)| {
+                o.saturating_sub(m)
+            });
+        MatchScore::new(distance, err, matched, offset)
+
+        // TODO: old matching logic, consider what to keep
+        // // Get the model and other model points
+        // let model_points = self.trace.points();
+        // let other_points = other.trace.points();
+        // let model_count = model_points.len();
+        // let other_count = other_points.len();
+
+        // // Skip if the template has more points than our current trace
+        // if other_count < model_count {
+        //     return false;
+        // }
+
+        // // Determine how many points to process, minimum length wins
+        // let count = min(model_points.len(), other_points.len());
+
+        // // Select the last points based on the determined count to use
+        // let model_points = &model_points[model_count - count..model_count];
+        // let other_points = &other_points[other_count - count..other_count];
+
+        // let model_points_cum: Vec<f64> = model_points
+        //     .iter()
+        //     .scan(0.0, |acc, p| {
+        //         *acc += p.radians();
+        //         Some(*acc)
+        //     })
+        //     .collect();
+        // let other_points_cum: Vec<f64> = other_points
+        //     .iter()
+        //     .scan(0.0, |acc, p| {
+        //         *acc += p.radians();
+        //         Some(*acc)
+        //     })
+        //     .collect();
+
+        // // Calculate the difference for each point
+        // let diff = model_points
+        //     .iter()
+        //     .rev()
+        //     .zip(other_points.iter().rev())
+        //     .map(|(a, b)| rad_diff(b.radians(), a.radians()));
+
+        // // Calculate the cumulative difference for each point
+        // let diff_inc_abs_diff: Vec<f64> = model_points_cum
+        //     .iter()
+        //     .rev()
+        //     .zip(other_points_cum.iter().rev())
+        //     .map(|(a, b)| (a - b).abs())
+        //     .collect();
+
+        // // Calculate the cumulative difference on each point
+        // let cum_diff: Vec<f64> = diff
+        //     .scan(0.0, |acc, p| {
+        //         *acc += p;
+        //         Some(*acc)
+        //     })
+        //     .collect();
+
+        // // Skip if the total difference is too big
+        // if cum_diff.last().unwrap().abs() > TOTAL_DIFF_MAX {
+        //     return false;
+        // }
+
+        // if diff_inc_abs_diff
+        //     .windows(8)
+        //     .any(|p| p.iter().filter(|p| *p > &0.3).count() > 6)
+        // {
+        //     return false;
+        // }
+
+        // // Skip if any of the points has a difference of more than 2
+        // if cum_diff.iter().any(|p| p.abs() > POINT_DIFF_MAX) {
+        //     return false;
+        // }
+
+        // // Skip if each window of 5 points has an average difference bigger than 1
+        // if GROUP_SIZE > 0 && cum_diff
+        //     .windows(GROUP_SIZE)
+        //     .any(|p| (p.iter().sum::<f64>().abs() / GROUP_SIZE as f64) > GROUP_DIFF_MAX)
+        // {
+        //     return false;
+        // }
+    }
+}
+
+/// A rotational trace stream, building a rotational trace incrementally as points arrive.
+///
+/// Points are resampled and converted into rotational points at a constant cost for each point,
+/// however long the trace grows. The rotational points produced are the same as
+/// `PointTrace::to_rot_trace(true)` produces for the same points, if projected onto the XY plane.
+#[derive(Clone, Debug, Default)]
+pub struct RotTraceStream {
+    /// The drawing plane sampled points are projected onto.
+    plane: Plane,
+
+    /// The resampler, keeping track of the last sample.
+    sampler: StreamSampler,
+
+    /// The last sampled point, in the space of the drawing plane.
+    last_point: Option<NPoint3>,
+
+    /// The last edge between sampled points.
+    last_edge: Option<NVector3>,
+
+    /// The number of rotational points produced by each pushed point, oldest first.
+    produced: VecDeque<usize>,
+}
+
+impl RotTraceStream {
+    /// Construct a new empty stream, projecting points onto the XY plane.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a new empty stream, projecting points onto the given drawing `plane`.
+    pub fn with_plane(plane: Plane) -> Self {
+        Self {
+            plane,
+            ..Self::default()
+        }
+    }
+
+    /// Get the drawing plane points are projected onto.
+    pub fn plane(&self) -> Plane {
+        self.plane
+    }
+
+    /// Push a new `point`, and add the rotational points it produces to the given `trace`.
+    ///
+    /// See `PointTrace::calc_rot_points_iter` for details on the calculation.
+    pub fn push(&mut self, point: Point3, trace: &mut RotTrace) {
+        let len = trace.len();
+        let plane = self.plane;
+        let last_point = &mut self.last_point;
+        let last_edge = &mut self.last_edge;
+
+        self.sampler.push(point.to_npoint(), |sample| {
+            let sample = plane.project(&sample);
+            if let Some(prev) = last_point.replace(sample) {
+                let edge = sample - prev;
+                if let Some(prev_edge) = last_edge.replace(edge) {
+                    trace.push(calc_rot_point(&prev_edge, &edge));
+                }
+            }
+        });
+
+        self.produced.push_back(trace.len() - len);
+    }
+
+    /// Forget the given number of oldest pushed points, and remove the rotational points they
+    /// produced from the given `trace`.
+    ///
+    /// This keeps the stream in sync with a truncated point trace. The sampling of newer points
+    /// is kept as is, so the remaining trace may slightly differ from recalculating it.
+    /// The number of removed rotational points is returned.
+    pub fn remove_oldest(&mut self, count: usize, trace: &mut RotTrace) -> usize {
+        let count = min(count, self.produced.len());
+        let removed = self.produced.drain(..count).sum();
+        trace.remove_oldest(removed);
+        removed
+    }
+
+    /// Reset the stream, to start a new trace in the same drawing plane.
+    pub fn reset(&mut self) {
+        *self = Self::with_plane(self.plane);
+    }
+}
+
+/// Calculate the rotational point between the edge `a` and the next edge `b`, both in the space of
+/// the drawing plane.
+///
+/// The turn is the change of heading of the edges projected onto the drawing plane, the pitch is
+/// the angle of edge `a` out of the plane. Edges pointing almost straight out of the plane don't
+/// turn, as described by `config::trace::MIN_PLANAR_RATIO`. The distance is the length of edge
+/// `a` projected onto the plane.
+#[inline]
+fn calc_rot_point(a: &NVector3, b: &NVector3) -> RotPoint {
+    let (planar_a, planar_b) = (NVector2::new(a.x, a.y), NVector2::new(b.x, b.y));
+    let angle = if planar_a.norm() >= a.norm() * MIN_PLANAR_RATIO
+        && planar_b.norm() >= b.norm() * MIN_PLANAR_RATIO
+    {
+        rad_diff(planar_b.y.atan2(planar_b.x), planar_a.y.atan2(planar_a.x))
+    } else {
+        0.0
+    };
+    RotPoint::spatial(angle, a.z.atan2(planar_a.norm()), planar_a.norm())
 }
 
 impl fmt::Display for PointTrace {
@@ -198,7 +749,7 @@ impl fmt::Display for PointTrace {
         let p = self
             .points
             .iter()
-            .map(|p| format!("{}", p))
+            .map(|p| format!("{}", p.point))
             .collect::<Vec<_>>()
             .join(", ");
 
@@ -277,6 +828,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn timed() {
+        let mut points = PointTrace::from_timed(vec![
+            TimedPoint3::new(Point3::new(0.0, 0.0, 0.0), 0),
+            TimedPoint3::new(Point3::new(0.0, 30.0, 0.0), 500_000),
+            TimedPoint3::new(Point3::new(40.0, 30.0, 0.0), 1_000_000),
+            TimedPoint3::new(Point3::new(40.0, 0.0, 0.0), 2_000_000),
+        ]);
+
+        assert_eq!(points.duration(), 2_000_000);
+        assert_eq!(points.length(), 100.0);
+        assert_eq!(points.velocity(), Some(50.0));
+
+        points.truncate_age(1_000_000);
+        assert_eq!(points.duration(), 1_000_000);
+        assert_eq!(points.velocity(), Some(30.0));
+
+        assert_eq!(PointTrace::new(vec![Point3::zero(); 2]).velocity(), None);
+    }
+
+    #[test]
+    fn stream() {
+        // Spiral with varying steps, some shorter and some longer than the sample distance
+        let points: Vec<Point3> = (0..200)
+            .map(|i| i as f64)
+            .map(|i| {
+                let (radius, angle) = (20.0 + i, i * i / 500.0);
+                Point3::new(radius * angle.cos(), radius * angle.sin(), i % 3.0)
+            })
+            .collect();
+
+        let mut stream = RotTraceStream::new();
+        let mut trace = RotTrace::empty();
+        for point in &points {
+            stream.push(*point, &mut trace);
+        }
+
+        assert!(trace.len() > 0);
+        assert_eq!(trace, PointTrace::new(points).to_rot_trace(true));
+    }
+
+    #[test]
+    fn spatial() {
+        // A circle, and a corkscrew pushing forward with the same projection onto the XY plane
+        let trace = |depth: f64| {
+            let points = (0..300)
+                .map(|i| i as f64 / 20.0)
+                .map(|a| Point3::new(60.0 * a.cos(), 60.0 * a.sin(), -depth * a))
+                .collect();
+            let trace = PointTrace::new(points).to_rot_trace(true);
+            let model = trace.slice(trace.len() - 50, trace.len());
+            (trace, model)
+        };
+        let (circle, circle_model) = trace(0.0);
+        let (corkscrew, corkscrew_model) = trace(40.0);
+        assert!(corkscrew.points().iter().all(|p| p.pitch() < -0.5));
+
+        // Both match in 2D, but only match themselves in 3D
+        assert!(circle_model.matches(&corkscrew, false));
+        assert!(circle_model.matches(&circle, true));
+        assert!(corkscrew_model.matches(&corkscrew, true));
+        assert!(!circle_model.matches(&corkscrew, true));
+        assert!(!corkscrew_model.matches(&circle, true));
+    }
+
+    #[test]
+    fn scale_invariant() {
+        // Clockwise circles of different sizes, after a straight lead in
+        let circle = |radius: f64| {
+            let lead_in = (0..20).map(|i| Point3::new(i as f64 * -10.0, radius, 0.0));
+            let circle = (0..=100)
+                .map(|i| i as f64 / 100.0 * 2.0 * ::std::f64::consts::PI)
+                .map(|a| Point3::new(radius * a.sin(), radius * a.cos(), 0.0));
+            PointTrace::new(lead_in.rev().chain(circle).collect()).to_rot_trace(true)
+        };
+        let (small, big) = (circle(30.0), circle(90.0));
+        let model = small.slice(small.len() - 19, small.len());
+        let scaled = model.resample(40);
+        assert_eq!(scaled.len(), 40);
+        let turn = |trace: &RotTrace| trace.points().iter().map(|p| p.radians()).sum::<f64>();
+        assert!((turn(&scaled) - turn(&model)).abs() < 1.0e-9);
+
+        // Only the scale invariant model matches the big circle, either at the end of its trace
+        // or as a whole stroke
+        assert!(!model.matches(&big, false));
+        assert!(scaled.matches_scaled(&small, false, false));
+        assert!(scaled.matches_scaled(&big, false, false));
+        let stroke = big.slice(big.len() - 57, big.len());
+        assert!(scaled.matches_scaled(&stroke, true, false));
+        assert!(!scaled.matches_scaled(&big.slice(0, 20), false, false));
+    }
+
+    #[test]
+    fn score() {
+        // A circle, with a model taken from its end
+        let points = (0..200)
+            .map(|i| i as f64 / 20.0)
+            .map(|a| Point3::new(60.0 * a.cos(), 60.0 * a.sin(), 0.0))
+            .collect();
+        let circle = PointTrace::new(points).to_rot_trace(true);
+        let model = circle.slice(circle.len() - 40, circle.len());
+
+        // The model matches at the end of the circle, with every point
+        let score = model.score(&circle, false);
+        assert!(score.is_match() && score.errors() == 0);
+        assert_eq!(score.matched().len(), 40);
+        assert_eq!(score.matched()[39], (39, circle.len() - 1));
+
+        // A mirrored circle turns the other way, and is a bad mismatch
+        let mismatch = model.score(&circle.mirrored(), false);
+        assert!(!mismatch.is_match() && mismatch.errors() >= MAX_ERROR);
+        assert!(score.is_better_than(&mismatch) && mismatch.distance() > 0.75);
+    }
+
     #[bench]
     fn corner_bench(b: &mut Bencher) {
         let points = PointTrace::new(vec![
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use config::zone::{BOX_MAX, BOX_MIN, DEPTH};
+use types::Point3;
+
+/// An interaction zone, the region in the shared coordinate space points are tracked in.
+///
+/// Points outside the zone aren't tracked, so users can reach into the zone to start drawing
+/// gestures, and move their hand out of it to stop.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Zone {
+    /// Points are tracked anywhere.
+    Any,
+
+    /// Points are tracked within an axis aligned box, between the `min` and `max` corners.
+    Box { min: Point3, max: Point3 },
+
+    /// Points are tracked when reaching forward past a depth threshold, with a Z coordinate of
+    /// at most `z`.
+    Depth { z: f64 },
+}
+
+impl Zone {
+    /// Check whether the given point is within this zone.
+    pub fn contains(&self, point: Point3) -> bool {
+        match self {
+            Zone::Any => true,
+            Zone::Box { min, max } => {
+                min.x <= point.x
+                    && point.x <= max.x
+                    && min.y <= point.y
+                    && point.y <= max.y
+                    && min.z <= point.z
+                    && point.z <= max.z
+            }
+            Zone::Depth { z } => point.z <= *z,
+        }
+    }
+}
+
+impl Default for Zone {
+    fn default() -> Self {
+        Zone::Any
+    }
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    /// Parse a zone, being `any`, `box` optionally followed by a colon and the comma separated
+    /// coordinates of both corners, or `depth` optionally followed by a colon and the threshold.
+    ///
+    /// For example: `box:-150,100,-150,150,350,100` or `depth:-20`. Values that aren't given use
+    /// their default from `config::zone`.
+    fn from_str(zone: &str) -> Result<Self, Self::Err> {
+        let mut parts = zone.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let values = parts
+            .next()
+            .map(|values| {
+                values
+                    .split(',')
+                    .map(|value| {
+                        value
+                            .trim()
+                            .parse::<f64>()
+                            .map_err(|_| format!("invalid zone value '{}'", value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap_or_else(|| Ok(vec![]))?;
+
+        match (kind, values.as_slice()) {
+            ("any", []) => Ok(Zone::Any),
+            ("box", []) => Ok(Zone::Box {
+                min: Point3::new(BOX_MIN[0], BOX_MIN[1], BOX_MIN[2]),
+                max: Point3::new(BOX_MAX[0], BOX_MAX[1], BOX_MAX[2]),
+            }),
+            ("box", [x1, y1, z1, x2, y2, z2]) => Ok(Zone::Box {
+                min: Point3::new(x1.min(*x2), y1.min(*y2), z1.min(*z2)),
+                max: Point3::new(x1.max(*x2), y1.max(*y2), z1.max(*z2)),
+            }),
+            ("depth", []) => Ok(Zone::Depth { z: DEPTH }),
+            ("depth", [z]) => Ok(Zone::Depth { z: *z }),
+            ("box", _) => Err("a box zone must have 6 coordinates".into()),
+            ("depth", _) => Err("a depth zone must have a single threshold".into()),
+            _ => Err(format!(
+                "invalid zone '{}', must be 'any', 'box' or 'depth'",
+                zone
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_zone() {
+        let zone: Zone = "box:100,0,50,-100,200,-50".parse().unwrap();
+        assert_eq!(
+            zone,
+            Zone::Box {
+                min: Point3::new(-100.0, 0.0, -50.0),
+                max: Point3::new(100.0, 200.0, 50.0),
+            },
+        );
+        assert!(zone.contains(Point3::new(0.0, 100.0, 0.0)));
+        assert!(!zone.contains(Point3::new(0.0, 250.0, 0.0)));
+
+        let zone: Zone = "depth:-20".parse().unwrap();
+        assert!(zone.contains(Point3::new(0.0, 0.0, -30.0)));
+        assert!(!zone.contains(Point3::new(0.0, 0.0, 0.0)));
+        assert_eq!("depth".parse(), Ok(Zone::Depth { z: DEPTH }));
+
+        assert!("any".parse::<Zone>().unwrap().contains(Point3::zero()));
+        assert!("box:1,2,3".parse::<Zone>().is_err());
+        assert!("depth:x".parse::<Zone>().is_err());
+        assert!("sphere".parse::<Zone>().is_err());
+    }
+}
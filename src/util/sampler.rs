@@ -75,6 +75,47 @@ where
     }
 }
 
+/// A resampler that resamples points one at a time as they come in, keeping state in between.
+///
+/// This produces the same samples as the `Sampler` iterator does for the same points, but allows
+/// resampling a growing trace without walking through all of its points again.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct StreamSampler {
+    /// The last sampled point, used to determine where the next point can be sampled.
+    last: Option<Point3>,
+}
+
+impl StreamSampler {
+    /// Resample the given point `p`, invoking `f` for each new sampled point.
+    ///
+    /// A point may produce no samples if it's too close to the last sample, or multiple samples
+    /// if it's further away than the sample distance.
+    #[inline]
+    pub fn push<F>(&mut self, p: Point3, mut f: F)
+    where
+        F: FnMut(Point3),
+    {
+        let mut last = match self.last {
+            Some(last) => last,
+            None => {
+                // Sample the origin as first point
+                self.last.replace(p);
+                f(p);
+                return;
+            }
+        };
+
+        // Sample towards the point until it's closer than the sample distance
+        while (last - p).magnitude() >= SAMPLE_DISTANCE {
+            let vector = (p - last).normalize() * SAMPLE_DISTANCE;
+            last = last + vector;
+            f(last);
+        }
+
+        self.last.replace(last);
+    }
+}
+
 pub(crate) trait SamplerIter<I>
 where
     I: Iterator<Item = Point3>,
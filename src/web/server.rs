@@ -1,11 +1,23 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
 
 use rocket::{self, State};
 use rocket_contrib::{json::Json, serve::StaticFiles, templates::Template};
 
-use gesture::{GestureController, GestureState};
+use fragment::Segment;
+use gesture::{Detection, GestureController, GestureState};
+use sensor::calibrator::{SensorCalibration, WizardStatus};
+use sensor::status::StatusReport;
+use sensor::{
+    Calibrator, SensorController, SensorId, SensorSource, StatusMonitor, UdpSource, WebSocketSource,
+};
 use store::TemplateStore;
-use types::{Model, Template as GestureTemplate};
+use types::{
+    HandPart, Point3, PostureTemplate, RotTrace, Template as GestureTemplate, TemplateOptions, Zone,
+};
 
 pub struct Server {
     /// The gesture controller used for managing recordings.
@@ -13,6 +25,15 @@ pub struct Server {
 
     /// The template store.
     template_store: Arc<TemplateStore>,
+
+    /// The sensor controller, used for adding and removing sensors.
+    sensor_controller: Arc<Mutex<SensorController>>,
+
+    /// The sensor calibrator.
+    calibrator: Arc<Calibrator>,
+
+    /// The sensor status monitor.
+    monitor: Arc<StatusMonitor>,
 }
 
 impl Server {
@@ -20,10 +41,16 @@ impl Server {
     pub fn new(
         gesture_controller: Arc<GestureController>,
         template_store: Arc<TemplateStore>,
+        sensor_controller: Arc<Mutex<SensorController>>,
+        calibrator: Arc<Calibrator>,
+        monitor: Arc<StatusMonitor>,
     ) -> Server {
         Server {
             gesture_controller,
             template_store,
+            sensor_controller,
+            calibrator,
+            monitor,
         }
     }
 
@@ -36,18 +63,30 @@ impl Server {
                     index,
                     template_index,
                     create_template,
+                    create_posture,
                     add_builtin_templates,
                     delete_all_templates,
                     delete_template,
                     state,
                     set_state,
                     visualizer,
+                    sensor_status,
+                    add_sensor,
+                    remove_sensor,
+                    calibration,
+                    reset_calibration,
+                    start_calibration_wizard,
+                    capture_calibration_pose,
+                    cancel_calibration_wizard,
                 ],
             )
             .mount("/css", StaticFiles::from("res/static/css"))
             .mount("/js", StaticFiles::from("res/static/js"))
             .manage(self.gesture_controller.clone())
             .manage(self.template_store.clone())
+            .manage(self.sensor_controller.clone())
+            .manage(self.calibrator.clone())
+            .manage(self.monitor.clone())
             .attach(Template::fairing())
             .launch();
     }
@@ -63,17 +102,28 @@ fn index() -> Template {
 fn template_index(store: State<Arc<TemplateStore>>) -> Json<TemplateIndexResponse> {
     Json(TemplateIndexResponse {
         templates: store.to_templates(),
+        postures: store.to_postures(),
     })
 }
 
-#[get("/api/v1/template/create/<name>/<from>/<to>")]
+#[get("/api/v1/template/create/<name>/<from>/<to>/<options>")]
 fn create_template(
     name: String,
     from: usize,
     to: usize,
+    options: String,
     gesture_controller: State<Arc<GestureController>>,
 ) -> Json<bool> {
-    Json(gesture_controller.create(name, from, to).is_ok())
+    let options = match options.parse::<TemplateOptions>() {
+        Ok(options) => options,
+        Err(_) => return Json(false),
+    };
+    Json(gesture_controller.create(name, from, to, options).is_ok())
+}
+
+#[get("/api/v1/posture/create/<name>")]
+fn create_posture(name: String, gesture_controller: State<Arc<GestureController>>) -> Json<bool> {
+    Json(gesture_controller.create_posture(name).is_ok())
 }
 
 #[get("/api/v1/template/add_builtin")]
@@ -94,6 +144,7 @@ fn delete_all_templates(template_store: State<Arc<TemplateStore>>) -> Json<bool>
 #[derive(Serialize, Deserialize)]
 struct TemplateIndexResponse {
     templates: Vec<GestureTemplate>,
+    postures: Vec<PostureTemplate>,
 }
 
 #[get("/api/v1/state")]
@@ -130,15 +181,179 @@ struct StateResponse {
 #[get("/api/v1/visualizer")]
 fn visualizer(gesture_controller: State<Arc<GestureController>>) -> Json<LiveTraceResponse> {
     // Get the live data models and detected gestures
-    let models = gesture_controller.live_trace();
+    let models = gesture_controller
+        .live_trace()
+        .into_iter()
+        .map(|(part, trace, segments)| LiveModel {
+            part,
+            trace,
+            segments,
+        })
+        .collect();
     let detected = gesture_controller.flush_detected();
 
+    // Get the interaction zone, and the latest sensed positions relative to it
+    let zone = gesture_controller.zone();
+    let positions = gesture_controller
+        .live_positions()
+        .into_iter()
+        .map(|(part, point)| LivePosition {
+            part,
+            point,
+            inside: zone.contains(point),
+        })
+        .collect();
+
     // Respond with the state
-    Json(LiveTraceResponse { models, detected })
+    Json(LiveTraceResponse {
+        models,
+        detected,
+        zone,
+        positions,
+    })
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct LiveTraceResponse {
-    models: Vec<Model>,
-    detected: Vec<GestureTemplate>,
+    models: Vec<LiveModel>,
+    detected: Vec<Detection>,
+    zone: Zone,
+    positions: Vec<LivePosition>,
+}
+
+#[derive(Serialize)]
+struct LivePosition {
+    part: HandPart,
+    point: Point3,
+    inside: bool,
+}
+
+#[derive(Serialize)]
+struct LiveModel {
+    part: HandPart,
+    trace: RotTrace,
+    segments: Vec<Segment>,
+}
+
+#[get("/api/v1/sensor")]
+fn sensor_status(monitor: State<Arc<StatusMonitor>>) -> Json<SensorStatusResponse> {
+    Json(SensorStatusResponse {
+        sensors: monitor.reports(),
+    })
+}
+
+#[derive(Serialize)]
+struct SensorStatusResponse {
+    sensors: Vec<StatusReport>,
+}
+
+#[get("/api/v1/sensor/add/<kind>/<address>")]
+fn add_sensor(
+    kind: String,
+    address: String,
+    sensor_controller: State<Arc<Mutex<SensorController>>>,
+    calibrator: State<Arc<Calibrator>>,
+) -> Json<CalibrationResponse> {
+    // Build the network source to add
+    let address: SocketAddr = match address.parse() {
+        Ok(address) => address,
+        Err(_) => {
+            let error = Some(format!("invalid sensor address '{}'", address));
+            return Json(CalibrationResponse::new(&calibrator, error));
+        }
+    };
+    let source: Box<dyn SensorSource> = match kind.as_str() {
+        "udp" => Box::new(UdpSource::new(address)),
+        "websocket" => Box::new(WebSocketSource::new(address)),
+        _ => {
+            let error = Some(format!("invalid sensor kind '{}'", kind));
+            return Json(CalibrationResponse::new(&calibrator, error));
+        }
+    };
+
+    let error = sensor_controller
+        .lock()
+        .expect("failed to lock sensor controller")
+        .add(source, None)
+        .err()
+        .map(|err| format!("failed to start sensor: {}", err));
+    Json(CalibrationResponse::new(&calibrator, error))
+}
+
+#[get("/api/v1/sensor/<sensor>/remove")]
+fn remove_sensor(
+    sensor: SensorId,
+    sensor_controller: State<Arc<Mutex<SensorController>>>,
+    calibrator: State<Arc<Calibrator>>,
+) -> Json<CalibrationResponse> {
+    let removed = sensor_controller
+        .lock()
+        .expect("failed to lock sensor controller")
+        .remove(sensor);
+    let error = if removed {
+        None
+    } else {
+        Some("unknown sensor".into())
+    };
+    Json(CalibrationResponse::new(&calibrator, error))
+}
+
+#[get("/api/v1/calibration")]
+fn calibration(calibrator: State<Arc<Calibrator>>) -> Json<CalibrationResponse> {
+    Json(CalibrationResponse::new(&calibrator, None))
+}
+
+#[get("/api/v1/calibration/<sensor>/reset")]
+fn reset_calibration(
+    sensor: SensorId,
+    calibrator: State<Arc<Calibrator>>,
+) -> Json<CalibrationResponse> {
+    let error = if calibrator.reset(sensor) {
+        None
+    } else {
+        Some("failed to reset sensor calibration".into())
+    };
+    Json(CalibrationResponse::new(&calibrator, error))
+}
+
+#[get("/api/v1/calibration/<sensor>/wizard")]
+fn start_calibration_wizard(
+    sensor: SensorId,
+    calibrator: State<Arc<Calibrator>>,
+) -> Json<CalibrationResponse> {
+    let error = match calibrator.start_wizard(sensor) {
+        Some(_) => None,
+        None => Some("unknown sensor".into()),
+    };
+    Json(CalibrationResponse::new(&calibrator, error))
+}
+
+#[get("/api/v1/calibration/wizard/capture")]
+fn capture_calibration_pose(calibrator: State<Arc<Calibrator>>) -> Json<CalibrationResponse> {
+    let error = calibrator.capture().err();
+    Json(CalibrationResponse::new(&calibrator, error))
+}
+
+#[get("/api/v1/calibration/wizard/cancel")]
+fn cancel_calibration_wizard(calibrator: State<Arc<Calibrator>>) -> Json<CalibrationResponse> {
+    calibrator.cancel_wizard();
+    Json(CalibrationResponse::new(&calibrator, None))
+}
+
+#[derive(Serialize)]
+struct CalibrationResponse {
+    sensors: Vec<SensorCalibration>,
+    wizard: Option<WizardStatus>,
+    error: Option<String>,
+}
+
+impl CalibrationResponse {
+    /// Build a response with the current calibration state, and an optional error message.
+    fn new(calibrator: &Calibrator, error: Option<String>) -> Self {
+        CalibrationResponse {
+            sensors: calibrator.sensors(),
+            wizard: calibrator.wizard_status(),
+            error,
+        }
+    }
 }